@@ -1,6 +1,17 @@
+use musicxml_analysis::analysis::DensityMetrics;
+use musicxml_analysis::analysis::HarmonicMetrics;
+use musicxml_analysis::analysis::KeyMetrics;
+use musicxml_analysis::analysis::TupletMetrics;
 use musicxml_analysis::analysis::calculate_density_metrics;
 use musicxml_analysis::analysis::calculate_diversity_metrics;
-use musicxml_analysis::extraction::musicxml::extract_measure_data;
+use musicxml_analysis::analysis::calculate_grace_note_metrics;
+use musicxml_analysis::analysis::calculate_harmonic_metrics;
+use musicxml_analysis::analysis::calculate_key_metrics;
+use musicxml_analysis::analysis::calculate_tuplet_metrics;
+use musicxml_analysis::extraction::loader::load_score;
+use musicxml_analysis::extraction::musicxml::extract_timeline_with_options;
+use musicxml_analysis::extraction::options::{ExtractionOptions, MeasureOrder};
+use musicxml_analysis::model::{MeasureFill, TempoSegment, Timeline, TimelineMeasure};
 use musicxml_analysis::statistics::correlation::calculate_pearson_correlation;
 use plotly::{
     Layout, Plot, Scatter,
@@ -18,7 +29,22 @@ struct PieceData {
     peak_density: f64,
     pitch_diversity: u32,
     key_diversity: u32,
+    microtonal_diversity: u32,
+    percussion_diversity: u32,
     total_note_count: u32,
+    avg_onset_density: f64,
+    peak_onset_density: f64,
+    total_onset_count: u32,
+    avg_chord_size: f64,
+    max_chord_size: u32,
+    avg_grace_density: f64,
+    peak_grace_density: f64,
+    total_grace_note_count: u32,
+    tuplets: TupletMetrics,
+    harmony: HarmonicMetrics,
+    keys: KeyMetrics,
+    part_densities: Vec<(String, DensityMetrics)>,
+    tempo_sections: Vec<String>,
 }
 
 fn main() {
@@ -26,7 +52,7 @@ fn main() {
 
     if args.len() < 2 {
         eprintln!(
-            "Usage: {} [--output-dir <dir>] <path to musicxml file or directory>",
+            "Usage: {} [--output-dir <dir>] [--strict] [--performance-order] <path to musicxml file or directory>",
             args[0]
         );
         process::exit(1);
@@ -34,6 +60,10 @@ fn main() {
 
     // Parse arguments
     let mut output_dir = ".".to_string();
+    let mut options = ExtractionOptions {
+        lenient: true,
+        ..ExtractionOptions::default()
+    };
     let mut input_path = None;
 
     let mut i = 1;
@@ -47,6 +77,14 @@ fn main() {
                 output_dir = args[i + 1].clone();
                 i += 2;
             }
+            "--strict" => {
+                options.lenient = false;
+                i += 1;
+            }
+            "--performance-order" => {
+                options.measure_order = MeasureOrder::Performance;
+                i += 1;
+            }
             _ => {
                 input_path = Some(args[i].clone());
                 i += 1;
@@ -61,9 +99,9 @@ fn main() {
 
     let path = Path::new(&input_path);
     let piece_data = if path.is_file() {
-        vec![analyze_file(&input_path)]
+        vec![analyze_file(&input_path, &options)]
     } else if path.is_dir() {
-        analyze_directory(&input_path)
+        analyze_directory(&input_path, &options)
     } else {
         eprintln!("Error: '{input_path}' is not a valid file or directory");
         process::exit(1);
@@ -127,14 +165,14 @@ fn main() {
     }
 }
 
-fn analyze_file(file_path: &str) -> PieceData {
-    analyze_single_file(file_path).unwrap_or_else(|e| {
+fn analyze_file(file_path: &str, options: &ExtractionOptions) -> PieceData {
+    analyze_single_file(file_path, options).unwrap_or_else(|e| {
         eprintln!("Failed to analyze '{file_path}': {e}");
         process::exit(1);
     })
 }
 
-fn analyze_directory(dir_path: &str) -> Vec<PieceData> {
+fn analyze_directory(dir_path: &str, options: &ExtractionOptions) -> Vec<PieceData> {
     let files = find_musicxml_files(dir_path).unwrap_or_else(|e| {
         eprintln!("Error reading directory '{dir_path}': {e}");
         process::exit(1);
@@ -150,7 +188,7 @@ fn analyze_directory(dir_path: &str) -> Vec<PieceData> {
     let mut piece_data = Vec::new();
 
     for file_path in files {
-        match analyze_single_file(&file_path) {
+        match analyze_single_file(&file_path, options) {
             Ok(data) => piece_data.push(data),
             Err(e) => eprintln!("Failed to analyze '{file_path}': {e}"),
         }
@@ -159,15 +197,34 @@ fn analyze_directory(dir_path: &str) -> Vec<PieceData> {
     piece_data
 }
 
-fn analyze_single_file(file_path: &str) -> Result<PieceData, String> {
+fn analyze_single_file(file_path: &str, options: &ExtractionOptions) -> Result<PieceData, String> {
     println!("Analyzing: {file_path}");
 
-    let score =
-        musicxml::read_score_partwise(file_path).map_err(|e| format!("Parse error: {e}"))?;
+    let loaded = load_score(file_path).map_err(|e| format!("Parse error: {e}"))?;
 
-    let measure_data = extract_measure_data(&score);
+    let (timeline, warnings) =
+        extract_timeline_with_options(&loaded.score, &loaded.microtonal_alters, options)
+            .map_err(|e| format!("Analysis error: {e}"))?;
+    for warning in warnings {
+        eprintln!("  Warning: {warning}");
+    }
+    report_irregular_measures(&timeline);
+
+    let measure_data = timeline.to_measure_data();
     let density = calculate_density_metrics(&measure_data);
+    let grace_notes = calculate_grace_note_metrics(&measure_data);
     let diversity = calculate_diversity_metrics(&measure_data);
+    let part_densities = timeline
+        .part_ids()
+        .into_iter()
+        .map(|part_id| {
+            let part_measure_data = timeline.to_part_measure_data(part_id);
+            (
+                part_id.to_string(),
+                calculate_density_metrics(&part_measure_data),
+            )
+        })
+        .collect();
 
     let name = Path::new(file_path)
         .file_stem()
@@ -181,10 +238,63 @@ fn analyze_single_file(file_path: &str) -> Result<PieceData, String> {
         peak_density: density.peak_notes_per_second,
         pitch_diversity: diversity.total_unique_pitches,
         key_diversity: diversity.total_unique_keys,
+        microtonal_diversity: diversity.total_unique_microtonal_pitches,
+        percussion_diversity: diversity.total_unique_percussion_instruments,
         total_note_count: density.total_note_count,
+        avg_onset_density: density.average_onsets_per_second,
+        peak_onset_density: density.peak_onsets_per_second,
+        total_onset_count: density.total_onset_count,
+        avg_chord_size: density.average_chord_size,
+        max_chord_size: density.max_chord_size,
+        avg_grace_density: grace_notes.average_grace_notes_per_second,
+        peak_grace_density: grace_notes.peak_grace_notes_per_second,
+        total_grace_note_count: grace_notes.total_grace_note_count,
+        tuplets: calculate_tuplet_metrics(&measure_data),
+        harmony: calculate_harmonic_metrics(&timeline),
+        keys: calculate_key_metrics(&timeline),
+        part_densities,
+        tempo_sections: describe_tempo_sources(&timeline),
     })
 }
 
+// One line per run of tempo changes that share a source
+fn describe_tempo_sources(timeline: &Timeline) -> Vec<String> {
+    let mut sections: Vec<(&TimelineMeasure, &TempoSegment)> = timeline.tempo_sections().collect();
+    sections.dedup_by(|(_, segment), (_, previous)| segment.source == previous.source);
+    sections
+        .into_iter()
+        .map(|(measure, segment)| describe_tempo_section(measure, segment))
+        .collect()
+}
+
+fn describe_tempo_section(measure: &TimelineMeasure, segment: &TempoSegment) -> String {
+    let tempo = match &segment.ramp {
+        Some(ramp) => format!(
+            "{:.0} to {:.0}",
+            segment.tempo.quarter_notes_per_minute(),
+            ramp.end_tempo.quarter_notes_per_minute()
+        ),
+        None => format!("{:.0}", segment.tempo.quarter_notes_per_minute()),
+    };
+    format!(
+        "measure {}: {tempo} quarter notes/minute from {}",
+        measure.number, segment.source
+    )
+}
+
+fn report_irregular_measures(timeline: &Timeline) {
+    for (measure, part) in timeline.irregular_measures() {
+        let problem = match part.fill {
+            MeasureFill::Overfull => "overfull",
+            _ => "incomplete",
+        };
+        eprintln!(
+            "  Warning: measure {} of part {} is {problem}",
+            measure.number, part.part_id
+        );
+    }
+}
+
 fn find_musicxml_files(dir_path: &str) -> Result<Vec<String>, std::io::Error> {
     let mut files = Vec::new();
 
@@ -210,9 +320,104 @@ fn print_piece_results(piece: &PieceData) {
     println!("  Average: {:>5.2} notes/second", piece.avg_density);
     println!("  Peak   : {:>5.2} notes/second", piece.peak_density);
     println!("  # notes: {}", piece.total_note_count);
-    println!("Pitch Diversity:");
-    println!("  # unique pitches: {}", piece.pitch_diversity);
-    println!("  # unique piano keys: {}", piece.key_diversity);
+    if piece.part_densities.len() > 1 {
+        for (part_id, density) in &piece.part_densities {
+            println!(
+                "  Part {part_id}: {:>5.2} average, {:>5.2} peak notes/second",
+                density.average_notes_per_second, density.peak_notes_per_second
+            );
+        }
+    }
+    println!("Onset Density:");
+    println!("  Average: {:>5.2} onsets/second", piece.avg_onset_density);
+    println!("  Peak   : {:>5.2} onsets/second", piece.peak_onset_density);
+    println!("  # onsets: {}", piece.total_onset_count);
+    if piece.max_chord_size > 0 {
+        println!(
+            "  Chord size: {:.2} average, {} max",
+            piece.avg_chord_size, piece.max_chord_size
+        );
+    }
+    if piece.total_grace_note_count > 0 {
+        println!("Grace Notes:");
+        println!(
+            "  Average: {:>5.2} grace notes/second",
+            piece.avg_grace_density
+        );
+        println!(
+            "  Peak   : {:>5.2} grace notes/second",
+            piece.peak_grace_density
+        );
+        println!("  # grace notes: {}", piece.total_grace_note_count);
+    }
+    if piece.tuplets.tuplet_note_count > 0 {
+        let ratios: Vec<String> = piece
+            .tuplets
+            .tuplet_ratios
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        println!("Tuplets:");
+        println!(
+            "  # tuplet notes: {} ({:.1}% of notes, {} nested)",
+            piece.tuplets.tuplet_note_count,
+            piece.tuplets.tuplet_note_share * 100.0,
+            piece.tuplets.nested_tuplet_note_count
+        );
+        println!("  Ratios: {}", ratios.join(", "));
+        println!(
+            "  Measures with cross-rhythms: {}",
+            piece.tuplets.cross_rhythm_measures
+        );
+    }
+    if piece.harmony.sonority_count > 0 {
+        println!("Harmony:");
+        println!("  # sonorities: {}", piece.harmony.sonority_count);
+        println!(
+            "  Chord vocabulary: {} distinct chords",
+            piece.harmony.chord_vocabulary_size
+        );
+        println!(
+            "  Chord changes: {:>5.2} changes/second",
+            piece.harmony.chord_changes_per_second
+        );
+        println!(
+            "  Non-tertian: {:.1}% of sonorities",
+            piece.harmony.non_tertian_share * 100.0
+        );
+    }
+    if let Some(opening) = piece.keys.window_keys.first() {
+        println!("Keys:");
+        println!("  Opening key: {}", opening.key);
+        println!(
+            "  # modulations: {} ({} steps around the circle of fifths)",
+            piece.keys.modulation_count, piece.keys.circle_of_fifths_distance
+        );
+        println!(
+            "  Differs from key signature: {:.1}% of windows",
+            piece.keys.key_signature_disagreement_share * 100.0
+        );
+    }
+    println!("Tempo:");
+    for section in &piece.tempo_sections {
+        println!("  {section}");
+    }
+    // Drum parts have no pitches, only kit pieces
+    if piece.pitch_diversity > 0 || piece.percussion_diversity == 0 {
+        println!("Pitch Diversity:");
+        println!("  # unique pitches: {}", piece.pitch_diversity);
+        println!("  # unique piano keys: {}", piece.key_diversity);
+        if piece.microtonal_diversity > 0 {
+            println!(
+                "  # unique microtonal pitches: {}",
+                piece.microtonal_diversity
+            );
+        }
+    }
+    if piece.percussion_diversity > 0 {
+        println!("Percussion Diversity:");
+        println!("  # unique instruments: {}", piece.percussion_diversity);
+    }
 }
 
 fn generate_note_density_histogram(
@@ -2,6 +2,11 @@
 pub struct DensityMetrics {
     pub average_notes_per_second: f64,
     pub peak_notes_per_second: f64,
-    pub peak_measure: u32,
+    pub peak_measure: Option<String>, // number as written, None when no measure has notes
     pub total_note_count: u32,
+    pub average_onsets_per_second: f64, // attacks, a chord counts once
+    pub peak_onsets_per_second: f64,
+    pub total_onset_count: u32,
+    pub average_chord_size: f64, // 0.0 when the piece has no chords
+    pub max_chord_size: u32,
 }
@@ -1,5 +1,7 @@
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub struct DiversityMetrics {
     pub total_unique_pitches: u32,
-    pub total_unique_keys: u32,
+    pub total_unique_keys: u32, // microtonal pitches have no key
+    pub total_unique_microtonal_pitches: u32,
+    pub total_unique_percussion_instruments: u32, // kit pieces, in place of pitches for drums
 }
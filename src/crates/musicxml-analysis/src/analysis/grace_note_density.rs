@@ -0,0 +1,97 @@
+use super::GraceNoteMetrics;
+use crate::model::MeasureData;
+
+pub fn calculate_grace_note_metrics(measure_data: &[MeasureData]) -> GraceNoteMetrics {
+    let mut total_grace_note_count = 0;
+    let mut total_duration_seconds = 0.0;
+    let mut peak_grace_notes_per_second = 0.0;
+    let mut peak_measure: Option<&str> = None;
+
+    for data in measure_data {
+        total_grace_note_count += data.grace_note_count;
+        total_duration_seconds += data.get_measure_duration_seconds();
+
+        let grace_notes_per_second =
+            data.grace_note_count as f64 / data.get_measure_duration_seconds();
+        if grace_notes_per_second > peak_grace_notes_per_second {
+            peak_grace_notes_per_second = grace_notes_per_second;
+            peak_measure = Some(&data.number);
+        }
+    }
+
+    let average_grace_notes_per_second = if total_duration_seconds > 0.0 {
+        total_grace_note_count as f64 / total_duration_seconds
+    } else {
+        0.0
+    };
+
+    GraceNoteMetrics {
+        average_grace_notes_per_second,
+        peak_grace_notes_per_second,
+        peak_measure: peak_measure.map(str::to_string),
+        total_grace_note_count,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use assert_float_eq::assert_float_absolute_eq;
+
+    use crate::model::{Tempo, TimeSignature, TupletUsage};
+
+    use super::*;
+
+    #[test]
+    fn test_calculate_grace_note_metrics_empty_data() {
+        // Arrange
+        let measure_data = vec![];
+
+        // Act
+        let metrics = calculate_grace_note_metrics(&measure_data);
+
+        // Assert
+        assert_float_absolute_eq!(metrics.average_grace_notes_per_second, 0.0);
+        assert_float_absolute_eq!(metrics.peak_grace_notes_per_second, 0.0);
+        assert_eq!(metrics.peak_measure, None);
+        assert_eq!(metrics.total_grace_note_count, 0);
+    }
+
+    #[test]
+    fn test_calculate_grace_note_metrics_finds_densest_measure() {
+        // Arrange: a pickup numbered 0 comes first
+        let measure_data = vec![
+            create_measure_data_with_grace_notes("0", 1),
+            create_measure_data_with_grace_notes("1", 0),
+            create_measure_data_with_grace_notes("2", 5),
+        ];
+
+        // Act
+        let metrics = calculate_grace_note_metrics(&measure_data);
+
+        // Assert
+        // 120 BPM in 4/4 = 2 seconds per measure
+        assert_float_absolute_eq!(metrics.average_grace_notes_per_second, 1.0);
+        assert_float_absolute_eq!(metrics.peak_grace_notes_per_second, 2.5);
+        assert_eq!(metrics.peak_measure.as_deref(), Some("2"));
+        assert_eq!(metrics.total_grace_note_count, 6);
+    }
+
+    fn create_measure_data_with_grace_notes(number: &str, grace_note_count: u32) -> MeasureData {
+        MeasureData {
+            number: number.to_string(),
+            note_count: 4,
+            onset_count: 4,
+            chord_sizes: Vec::new(),
+            grace_note_count,
+            tuplets: TupletUsage::default(),
+            tempo: Tempo::quarter_notes(120.0),
+            time_signature: TimeSignature::new(4, 4),
+            length_quarters: 4.0,
+            pitches: HashSet::new(),
+            sounding_pitches: HashSet::new(),
+            percussion_instruments: HashSet::new(),
+        }
+    }
+}
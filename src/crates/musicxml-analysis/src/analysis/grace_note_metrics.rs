@@ -0,0 +1,7 @@
+#[derive(Debug, PartialEq)]
+pub struct GraceNoteMetrics {
+    pub average_grace_notes_per_second: f64,
+    pub peak_grace_notes_per_second: f64,
+    pub peak_measure: Option<String>, // number as written, None when no measure has grace notes
+    pub total_grace_note_count: u32,
+}
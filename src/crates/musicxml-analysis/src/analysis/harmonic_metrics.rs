@@ -0,0 +1,7 @@
+#[derive(Debug, PartialEq)]
+pub struct HarmonicMetrics {
+    pub sonority_count: u32, // three or more pitch classes sounding together, repeats merged
+    pub chord_vocabulary_size: u32, // distinct chords by root and kind
+    pub chord_changes_per_second: f64,
+    pub non_tertian_share: f64, // 0.0 when the piece has no sonorities
+}
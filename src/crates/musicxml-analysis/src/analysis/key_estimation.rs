@@ -0,0 +1,445 @@
+use super::{KeyMetrics, WindowKey};
+use crate::model::{
+    ChromaticNoteName, Key, KeyMode, KeySignature, NoteEvent, NoteKind, PianoKey, Timeline,
+};
+use crate::statistics::correlation::calculate_pearson_correlation;
+
+// Windows overlap by half, so a modulation is caught close to where it happens
+const WINDOW_MEASURES: usize = 4;
+const WINDOW_HOP_MEASURES: usize = 2;
+
+// A single window often flips to a neighbouring key, the relative key most of
+// all, so a new key only counts as a modulation once it holds this many windows
+const MIN_WINDOWS_PER_MODULATION: usize = 2;
+
+// Krumhansl-Kessler key profiles, from the tonic upward by semitone
+const MAJOR_PROFILE: [f64; 12] = [
+    6.35, 2.23, 3.48, 2.33, 4.38, 4.09, 2.52, 5.19, 2.39, 3.66, 2.29, 2.88,
+];
+const MINOR_PROFILE: [f64; 12] = [
+    6.33, 2.68, 3.52, 5.38, 2.60, 3.53, 2.54, 4.75, 3.98, 2.69, 3.34, 3.17,
+];
+
+const CHROMATIC_NOTE_NAMES: [ChromaticNoteName; 12] = [
+    ChromaticNoteName::C,
+    ChromaticNoteName::CSharp,
+    ChromaticNoteName::D,
+    ChromaticNoteName::DSharp,
+    ChromaticNoteName::E,
+    ChromaticNoteName::F,
+    ChromaticNoteName::FSharp,
+    ChromaticNoteName::G,
+    ChromaticNoteName::GSharp,
+    ChromaticNoteName::A,
+    ChromaticNoteName::ASharp,
+    ChromaticNoteName::B,
+];
+
+pub fn calculate_key_metrics(timeline: &Timeline) -> KeyMetrics {
+    let durations = pitch_class_durations_per_measure(timeline);
+    let reference_part = reference_part(timeline);
+
+    let mut window_keys: Vec<WindowKey> = Vec::new();
+    for measure_index in window_starts(timeline.measures.len()) {
+        let window_end = (measure_index + WINDOW_MEASURES).min(durations.len());
+        let mut window_durations = [0.0; 12];
+        for measure_durations in &durations[measure_index..window_end] {
+            for (total, duration) in window_durations.iter_mut().zip(measure_durations) {
+                *total += duration;
+            }
+        }
+        let Some(key) = estimate_key(&window_durations) else {
+            continue;
+        };
+
+        let agrees_with_key_signature = reference_part.and_then(|(part_id, semitones)| {
+            let signature = written_key_signature(timeline, part_id, measure_index);
+            agrees_with_key_signature(&key, &signature, semitones)
+        });
+        window_keys.push(WindowKey {
+            measure_index,
+            key,
+            agrees_with_key_signature,
+        });
+    }
+
+    let modulations = find_modulations(&window_keys);
+    let circle_of_fifths_distance = modulations
+        .iter()
+        .map(|(from, to)| from.fifths_distance_to(to))
+        .sum();
+
+    let comparisons: Vec<bool> = window_keys
+        .iter()
+        .filter_map(|window| window.agrees_with_key_signature)
+        .collect();
+    let key_signature_disagreement_share = if comparisons.is_empty() {
+        0.0
+    } else {
+        comparisons.iter().filter(|agrees| !**agrees).count() as f64 / comparisons.len() as f64
+    };
+
+    KeyMetrics {
+        modulation_count: modulations.len() as u32,
+        circle_of_fifths_distance,
+        key_signature_disagreement_share,
+        window_keys,
+    }
+}
+
+// The key the piece opens with needs no run of windows to be established
+fn find_modulations(window_keys: &[WindowKey]) -> Vec<(Key, Key)> {
+    let mut modulations = Vec::new();
+    let mut established: Option<Key> = None;
+    for run in window_keys.chunk_by(|a, b| a.key == b.key) {
+        let key = run[0].key;
+        match established {
+            None => established = Some(key),
+            Some(from) if from != key && run.len() >= MIN_WINDOWS_PER_MODULATION => {
+                modulations.push((from, key));
+                established = Some(key);
+            }
+            Some(_) => {}
+        }
+    }
+    modulations
+}
+
+// Quarters each pitch class sounds in each measure, in concert pitch. Grace
+// notes, cue notes, unpitched and microtonal notes are left out.
+fn pitch_class_durations_per_measure(timeline: &Timeline) -> Vec<[f64; 12]> {
+    let mut durations = vec![[0.0; 12]; timeline.measures.len()];
+    for note in timeline.notes.iter().filter(|note| is_harmonic(note)) {
+        if let Some(key) = PianoKey::from_pitch(&note.sounding_pitch) {
+            durations[note.measure_index][key.note_name as usize] += note.duration.to_quarters();
+        }
+    }
+    durations
+}
+
+fn is_harmonic(note: &NoteEvent) -> bool {
+    note.kind == NoteKind::Regular && !note.is_unpitched
+}
+
+// Every WINDOW_HOP_MEASURES measures, plus a last window that ends with the piece
+fn window_starts(measure_count: usize) -> Vec<usize> {
+    if measure_count == 0 {
+        return Vec::new();
+    }
+
+    let last_start = measure_count.saturating_sub(WINDOW_MEASURES);
+    let mut starts: Vec<usize> = (0..=last_start).step_by(WINDOW_HOP_MEASURES).collect();
+    if starts.last() != Some(&last_start) {
+        starts.push(last_start);
+    }
+    starts
+}
+
+// The major or minor key whose profile correlates best with the durations,
+// None when nothing sounds
+fn estimate_key(durations: &[f64; 12]) -> Option<Key> {
+    if durations.iter().all(|duration| *duration == 0.0) {
+        return None;
+    }
+
+    let mut best: Option<(Key, f64)> = None;
+    for tonic in CHROMATIC_NOTE_NAMES {
+        for (mode, profile) in [
+            (KeyMode::Major, MAJOR_PROFILE),
+            (KeyMode::Minor, MINOR_PROFILE),
+        ] {
+            let rotated: Vec<f64> = (0..12)
+                .map(|pitch_class| profile[(pitch_class + 12 - tonic as usize) % 12])
+                .collect();
+            let correlation = calculate_pearson_correlation(durations, &rotated);
+            if best.is_none_or(|(_, best_correlation)| correlation > best_correlation) {
+                best = Some((Key::new(tonic, mode), correlation));
+            }
+        }
+    }
+    best.map(|(key, _)| key)
+}
+
+// The first part with pitched notes stands in for the written key of all parts,
+// together with how many semitones its first note sounds above its written pitch,
+// which is taken as its transposition throughout
+fn reference_part(timeline: &Timeline) -> Option<(&str, i32)> {
+    let note = timeline.notes.iter().find(|note| is_harmonic(note))?;
+    let cents = note.sounding_pitch.height_cents() - note.written_pitch.height_cents();
+    Some((note.part_id.as_str(), (cents as f64 / 100.0).round() as i32))
+}
+
+// The key signature in effect at the start of the measure, the last one written
+// in the measure when it changes there
+fn written_key_signature(timeline: &Timeline, part_id: &str, measure_index: usize) -> KeySignature {
+    timeline.measures[..=measure_index]
+        .iter()
+        .rev()
+        .flat_map(|measure| &measure.parts)
+        .filter(|part| part.part_id == part_id)
+        .find_map(|part| part.key_changes.last())
+        .map(|change| change.key.clone())
+        .unwrap_or_default()
+}
+
+// Compared in concert pitch. Without a mode the key signature agrees with both
+// the major key and its relative minor.
+fn agrees_with_key_signature(
+    key: &Key,
+    signature: &KeySignature,
+    transposition_semitones: i32,
+) -> Option<bool> {
+    let KeySignature::Traditional { fifths, mode } = signature else {
+        return None;
+    };
+    let concert_fifths = *fifths as i32 + transposition_semitones * 7;
+    let same_signature = (key.fifths() - concert_fifths).rem_euclid(12) == 0;
+    let same_mode = match mode {
+        Some(KeyMode::Major | KeyMode::Ionian) => key.mode == KeyMode::Major,
+        Some(KeyMode::Minor | KeyMode::Aeolian) => key.mode == KeyMode::Minor,
+        _ => true,
+    };
+    Some(same_signature && same_mode)
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_float_eq::assert_float_absolute_eq;
+    use rstest::rstest;
+
+    use super::*;
+    use crate::model::{
+        Accidental, Interval, KeyChange, MeasureFill, NoteName, PartMeasure, Pitch, RationalTime,
+        Tempo, TimeSignature, TimelineMeasure,
+    };
+
+    const C_MAJOR_SCALE: [(NoteName, Accidental); 8] = [
+        (NoteName::C, Accidental::Natural),
+        (NoteName::D, Accidental::Natural),
+        (NoteName::E, Accidental::Natural),
+        (NoteName::F, Accidental::Natural),
+        (NoteName::G, Accidental::Natural),
+        (NoteName::A, Accidental::Natural),
+        (NoteName::B, Accidental::Natural),
+        (NoteName::C, Accidental::Natural),
+    ];
+
+    const G_MAJOR_SCALE: [(NoteName, Accidental); 8] = [
+        (NoteName::G, Accidental::Natural),
+        (NoteName::A, Accidental::Natural),
+        (NoteName::B, Accidental::Natural),
+        (NoteName::C, Accidental::Natural),
+        (NoteName::D, Accidental::Natural),
+        (NoteName::E, Accidental::Natural),
+        (NoteName::F, Accidental::Sharp),
+        (NoteName::G, Accidental::Natural),
+    ];
+
+    const A_HARMONIC_MINOR_SCALE: [(NoteName, Accidental); 8] = [
+        (NoteName::A, Accidental::Natural),
+        (NoteName::B, Accidental::Natural),
+        (NoteName::C, Accidental::Natural),
+        (NoteName::D, Accidental::Natural),
+        (NoteName::E, Accidental::Natural),
+        (NoteName::F, Accidental::Natural),
+        (NoteName::G, Accidental::Sharp),
+        (NoteName::A, Accidental::Natural),
+    ];
+
+    #[test]
+    fn test_calculate_key_metrics_empty_timeline() {
+        // Arrange
+        let timeline = Timeline::default();
+
+        // Act
+        let metrics = calculate_key_metrics(&timeline);
+
+        // Assert
+        assert!(metrics.window_keys.is_empty());
+        assert_eq!(metrics.modulation_count, 0);
+        assert_eq!(metrics.circle_of_fifths_distance, 0);
+        assert_float_absolute_eq!(metrics.key_signature_disagreement_share, 0.0);
+    }
+
+    #[rstest]
+    #[case(&C_MAJOR_SCALE, Key::new(ChromaticNoteName::C, KeyMode::Major))]
+    #[case(&G_MAJOR_SCALE, Key::new(ChromaticNoteName::G, KeyMode::Major))]
+    #[case(&A_HARMONIC_MINOR_SCALE, Key::new(ChromaticNoteName::A, KeyMode::Minor))]
+    fn test_calculate_key_metrics_estimates_key_of_scale(
+        #[case] scale: &[(NoteName, Accidental)],
+        #[case] expected: Key,
+    ) {
+        // Arrange
+        let timeline = create_timeline(&[scale]);
+
+        // Act
+        let metrics = calculate_key_metrics(&timeline);
+
+        // Assert
+        assert_eq!(metrics.window_keys.len(), 1);
+        assert_eq!(metrics.window_keys[0].key, expected);
+    }
+
+    #[test]
+    fn test_calculate_key_metrics_counts_modulation() {
+        // Arrange: six measures of C major, then six of G major
+        let measures = [[&C_MAJOR_SCALE[..]; 6], [&G_MAJOR_SCALE[..]; 6]].concat();
+        let timeline = create_timeline(&measures);
+
+        // Act
+        let metrics = calculate_key_metrics(&timeline);
+
+        // Assert
+        let window_starts: Vec<usize> = metrics
+            .window_keys
+            .iter()
+            .map(|w| w.measure_index)
+            .collect();
+        assert_eq!(window_starts, vec![0, 2, 4, 6, 8]);
+        assert_eq!(metrics.modulation_count, 1);
+        assert_eq!(metrics.circle_of_fifths_distance, 1);
+    }
+
+    #[test]
+    fn test_calculate_key_metrics_counts_modulation_to_relative_key() {
+        // Arrange: six measures of C major, then six of A minor
+        let measures = [[&C_MAJOR_SCALE[..]; 6], [&A_HARMONIC_MINOR_SCALE[..]; 6]].concat();
+        let timeline = create_timeline(&measures);
+
+        // Act
+        let metrics = calculate_key_metrics(&timeline);
+
+        // Assert
+        assert_eq!(
+            metrics.window_keys.first().map(|w| w.key),
+            Some(Key::new(ChromaticNoteName::C, KeyMode::Major))
+        );
+        assert_eq!(
+            metrics.window_keys.last().map(|w| w.key),
+            Some(Key::new(ChromaticNoteName::A, KeyMode::Minor))
+        );
+        assert_eq!(metrics.modulation_count, 1);
+        assert_eq!(metrics.circle_of_fifths_distance, 0);
+    }
+
+    #[test]
+    fn test_calculate_key_metrics_does_not_count_key_of_a_single_window() {
+        // Arrange: C major, A minor, then C major again in the last window only
+        let measures = [
+            &[&C_MAJOR_SCALE[..]; 5][..],
+            &[&A_HARMONIC_MINOR_SCALE[..]; 2],
+            &[&C_MAJOR_SCALE[..]; 5],
+        ]
+        .concat();
+        let timeline = create_timeline(&measures);
+
+        // Act
+        let metrics = calculate_key_metrics(&timeline);
+
+        // Assert
+        let keys: Vec<Key> = metrics.window_keys.iter().map(|w| w.key).collect();
+        let c_major = Key::new(ChromaticNoteName::C, KeyMode::Major);
+        let a_minor = Key::new(ChromaticNoteName::A, KeyMode::Minor);
+        assert_eq!(keys, vec![c_major, a_minor, a_minor, a_minor, c_major]);
+        assert_eq!(metrics.modulation_count, 1);
+    }
+
+    #[test]
+    fn test_calculate_key_metrics_compares_with_key_signature() {
+        // Arrange: G major written without a key signature, then with one sharp
+        let mut timeline = create_timeline(&[&G_MAJOR_SCALE[..]; 8]);
+        timeline.measures[4].parts[0].key_changes = vec![create_key_change(1)];
+
+        // Act
+        let metrics = calculate_key_metrics(&timeline);
+
+        // Assert
+        let agreements: Vec<Option<bool>> = metrics
+            .window_keys
+            .iter()
+            .map(|w| w.agrees_with_key_signature)
+            .collect();
+        assert_eq!(agreements, vec![Some(false), Some(false), Some(true)]);
+        assert_float_absolute_eq!(metrics.key_signature_disagreement_share, 2.0 / 3.0);
+    }
+
+    #[test]
+    fn test_calculate_key_metrics_compares_transposing_part_in_concert_pitch() {
+        // Arrange: a B flat instrument writes D major with two sharps to sound C major
+        let mut timeline = create_timeline(&[&C_MAJOR_SCALE[..]]);
+        for note in &mut timeline.notes {
+            note.written_pitch = note
+                .sounding_pitch
+                .transposed(&Interval::from_semitones(1, 2));
+        }
+        timeline.measures[0].parts[0].key_changes = vec![create_key_change(2)];
+
+        // Act
+        let metrics = calculate_key_metrics(&timeline);
+
+        // Assert
+        assert_eq!(
+            metrics.window_keys[0].key,
+            Key::new(ChromaticNoteName::C, KeyMode::Major)
+        );
+        assert_eq!(metrics.window_keys[0].agrees_with_key_signature, Some(true));
+    }
+
+    fn create_key_change(fifths: i8) -> KeyChange {
+        KeyChange {
+            staff: None,
+            position_quarters: 0.0,
+            key: KeySignature::traditional(fifths, None),
+        }
+    }
+
+    // One measure of 4/4 per scale, in eighth notes from the fourth octave up
+    fn create_timeline(measures: &[&[(NoteName, Accidental)]]) -> Timeline {
+        let mut timeline_measures = Vec::new();
+        let mut notes = Vec::new();
+        for (measure_index, scale) in measures.iter().enumerate() {
+            timeline_measures.push(TimelineMeasure {
+                number: (measure_index + 1).to_string(),
+                tempo: Tempo::quarter_notes(120.0),
+                time_signature: TimeSignature::new(4, 4),
+                start: RationalTime::from_quarters(measure_index as i64 * 4),
+                length: RationalTime::from_quarters(4),
+                start_seconds: measure_index as f64 * 2.0,
+                implicit: false,
+                parts: vec![PartMeasure {
+                    part_id: "P1".to_string(),
+                    fill: MeasureFill::Complete,
+                    key_changes: Vec::new(),
+                    clef_changes: Vec::new(),
+                }],
+            });
+            for (index, (note_name, accidental)) in scale.iter().enumerate() {
+                let octave = if index == scale.len() - 1 { 5 } else { 4 };
+                let pitch = Pitch::new(*note_name, octave, *accidental);
+                notes.push(NoteEvent {
+                    part_id: "P1".to_string(),
+                    measure_index,
+                    voice: None,
+                    staff: 1,
+                    written_pitch: pitch,
+                    sounding_pitch: pitch,
+                    doubled_pitch: None,
+                    is_unpitched: false,
+                    instrument_id: None,
+                    onset: RationalTime::new(index as i64, 8),
+                    duration: RationalTime::new(1, 8),
+                    onset_seconds: measure_index as f64 * 2.0 + index as f64 * 0.25,
+                    duration_seconds: 0.25,
+                    is_chord_member: false,
+                    kind: NoteKind::Regular,
+                    tuplets: Vec::new(),
+                });
+            }
+        }
+        Timeline {
+            measures: timeline_measures,
+            notes,
+            ..Default::default()
+        }
+    }
+}
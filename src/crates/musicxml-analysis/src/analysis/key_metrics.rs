@@ -0,0 +1,18 @@
+use crate::model::Key;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowKey {
+    pub measure_index: usize, // first measure of the window
+    pub key: Key,
+    pub agrees_with_key_signature: Option<bool>, // None for non-traditional key signatures
+}
+
+#[derive(Debug, PartialEq)]
+pub struct KeyMetrics {
+    pub window_keys: Vec<WindowKey>, // windows without pitched notes are left out
+    pub modulation_count: u32, // changes of the estimated key that hold for two windows or more
+    pub circle_of_fifths_distance: u32, // summed over all modulations
+    // Of the windows, compared with the key signature of the first part with pitched
+    // notes only, in its concert pitch. 0.0 when no window can be compared.
+    pub key_signature_disagreement_share: f64,
+}
@@ -9,3 +9,27 @@ pub use diversity_metrics::DiversityMetrics;
 
 pub mod note_diversity;
 pub use note_diversity::calculate_diversity_metrics;
+
+pub mod grace_note_metrics;
+pub use grace_note_metrics::GraceNoteMetrics;
+
+pub mod grace_note_density;
+pub use grace_note_density::calculate_grace_note_metrics;
+
+pub mod tuplet_metrics;
+pub use tuplet_metrics::TupletMetrics;
+
+pub mod note_tuplets;
+pub use note_tuplets::calculate_tuplet_metrics;
+
+pub mod harmonic_metrics;
+pub use harmonic_metrics::HarmonicMetrics;
+
+pub mod note_harmony;
+pub use note_harmony::calculate_harmonic_metrics;
+
+pub mod key_metrics;
+pub use key_metrics::{KeyMetrics, WindowKey};
+
+pub mod key_estimation;
+pub use key_estimation::calculate_key_metrics;
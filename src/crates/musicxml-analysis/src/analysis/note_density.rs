@@ -6,34 +6,62 @@ pub fn calculate_density_metrics(measure_data: &[MeasureData]) -> DensityMetrics
         return DensityMetrics {
             average_notes_per_second: 0.0,
             peak_notes_per_second: 0.0,
-            peak_measure: 0,
+            peak_measure: None,
             total_note_count: 0,
+            average_onsets_per_second: 0.0,
+            peak_onsets_per_second: 0.0,
+            total_onset_count: 0,
+            average_chord_size: 0.0,
+            max_chord_size: 0,
         };
     }
 
     let mut total_note_count = 0;
+    let mut total_onset_count = 0;
     let mut total_duration_seconds = 0.0;
     let mut peak_notes_per_second = 0.0;
-    let mut peak_measure: u32 = 0;
+    let mut peak_onsets_per_second: f64 = 0.0;
+    let mut peak_measure: Option<&str> = None;
 
-    for (measure_index, data) in measure_data.iter().enumerate() {
+    for data in measure_data {
         total_note_count += data.note_count;
+        total_onset_count += data.onset_count;
         total_duration_seconds += data.get_measure_duration_seconds();
 
         let notes_per_second = data.note_count as f64 / data.get_measure_duration_seconds();
         if notes_per_second > peak_notes_per_second {
             peak_notes_per_second = notes_per_second;
-            peak_measure = (measure_index + 1) as u32;
+            peak_measure = Some(&data.number);
         }
+
+        let onsets_per_second = data.onset_count as f64 / data.get_measure_duration_seconds();
+        peak_onsets_per_second = peak_onsets_per_second.max(onsets_per_second);
     }
 
     let average_notes_per_second = total_note_count as f64 / total_duration_seconds;
+    let average_onsets_per_second = total_onset_count as f64 / total_duration_seconds;
+
+    let chord_sizes: Vec<u32> = measure_data
+        .iter()
+        .flat_map(|data| data.chord_sizes.iter().copied())
+        .collect();
+    let average_chord_size = if chord_sizes.is_empty() {
+        0.0
+    } else {
+        chord_sizes.iter().sum::<u32>() as f64 / chord_sizes.len() as f64
+    };
+    let max_chord_size = chord_sizes.iter().copied().max().unwrap_or(0);
 
     DensityMetrics {
         average_notes_per_second,
         peak_notes_per_second,
-        peak_measure,
+        peak_measure: peak_measure.map(str::to_string),
         total_note_count,
+        average_onsets_per_second,
+        peak_onsets_per_second,
+        total_onset_count,
+        average_chord_size,
+        max_chord_size,
     }
 }
 
@@ -43,7 +71,7 @@ mod tests {
 
     use assert_float_eq::assert_float_absolute_eq;
 
-    use crate::model::TimeSignature;
+    use crate::model::{Tempo, TimeSignature, TupletUsage};
 
     use super::*;
 
@@ -65,10 +93,18 @@ mod tests {
     fn test_calculate_density_metrics_zero_notes() {
         // Arrange
         let measure_data = vec![MeasureData {
+            number: "1".to_string(),
             note_count: 0,
-            tempo_bpm: 120.0,
+            onset_count: 0,
+            chord_sizes: Vec::new(),
+            grace_note_count: 0,
+            tuplets: TupletUsage::default(),
+            tempo: Tempo::quarter_notes(120.0),
             time_signature: TimeSignature::new(4, 4),
+            length_quarters: 4.0,
             pitches: HashSet::new(),
+            sounding_pitches: HashSet::new(),
+            percussion_instruments: HashSet::new(),
         }];
 
         // Act
@@ -77,7 +113,7 @@ mod tests {
         // Assert
         assert_float_absolute_eq!(metrics.average_notes_per_second, 0.0);
         assert_float_absolute_eq!(metrics.peak_notes_per_second, 0.0);
-        assert_eq!(metrics.peak_measure, 0);
+        assert_eq!(metrics.peak_measure, None);
         assert_eq!(metrics.total_note_count, 0);
     }
 
@@ -85,10 +121,18 @@ mod tests {
     fn test_calculate_density_metrics_single_measure() {
         // Arrange
         let measure_data = vec![MeasureData {
+            number: "1".to_string(),
             note_count: 2,
-            tempo_bpm: 120.0,
+            onset_count: 2,
+            chord_sizes: Vec::new(),
+            grace_note_count: 0,
+            tuplets: TupletUsage::default(),
+            tempo: Tempo::quarter_notes(120.0),
             time_signature: TimeSignature::new(4, 4),
+            length_quarters: 4.0,
             pitches: HashSet::new(),
+            sounding_pitches: HashSet::new(),
+            percussion_instruments: HashSet::new(),
         }];
 
         // Act
@@ -99,7 +143,7 @@ mod tests {
         // 2 notes in 2 seconds = 1.0 notes/sec
         assert_float_absolute_eq!(metrics.average_notes_per_second, 1.0);
         assert_float_absolute_eq!(metrics.peak_notes_per_second, 1.0);
-        assert_eq!(metrics.peak_measure, 1);
+        assert_eq!(metrics.peak_measure.as_deref(), Some("1"));
         assert_eq!(metrics.total_note_count, 2);
     }
 
@@ -108,16 +152,32 @@ mod tests {
         // Arrange
         let measure_data = vec![
             MeasureData {
+                number: "1".to_string(),
                 note_count: 1,
-                tempo_bpm: 120.0,
+                onset_count: 1,
+                chord_sizes: Vec::new(),
+                grace_note_count: 0,
+                tuplets: TupletUsage::default(),
+                tempo: Tempo::quarter_notes(120.0),
                 time_signature: TimeSignature::new(4, 4),
+                length_quarters: 4.0,
                 pitches: HashSet::new(),
+                sounding_pitches: HashSet::new(),
+                percussion_instruments: HashSet::new(),
             },
             MeasureData {
+                number: "2".to_string(),
                 note_count: 2,
-                tempo_bpm: 60.0,
+                onset_count: 2,
+                chord_sizes: Vec::new(),
+                grace_note_count: 0,
+                tuplets: TupletUsage::default(),
+                tempo: Tempo::quarter_notes(60.0),
                 time_signature: TimeSignature::new(4, 4),
+                length_quarters: 4.0,
                 pitches: HashSet::new(),
+                sounding_pitches: HashSet::new(),
+                percussion_instruments: HashSet::new(),
             },
         ];
 
@@ -131,7 +191,7 @@ mod tests {
         // Peak: 0.5 notes/sec (same for both measures)
         assert_float_absolute_eq!(metrics.average_notes_per_second, 0.5);
         assert_float_absolute_eq!(metrics.peak_notes_per_second, 0.5);
-        assert_eq!(metrics.peak_measure, 1);
+        assert_eq!(metrics.peak_measure.as_deref(), Some("1"));
         assert_eq!(metrics.total_note_count, 3);
     }
 
@@ -140,16 +200,32 @@ mod tests {
         // Arrange
         let measure_data = vec![
             MeasureData {
+                number: "1".to_string(),
                 note_count: 1,
-                tempo_bpm: 120.0,
+                onset_count: 1,
+                chord_sizes: Vec::new(),
+                grace_note_count: 0,
+                tuplets: TupletUsage::default(),
+                tempo: Tempo::quarter_notes(120.0),
                 time_signature: TimeSignature::new(4, 4),
+                length_quarters: 4.0,
                 pitches: HashSet::new(),
+                sounding_pitches: HashSet::new(),
+                percussion_instruments: HashSet::new(),
             },
             MeasureData {
+                number: "2".to_string(),
                 note_count: 1,
-                tempo_bpm: 120.0,
+                onset_count: 1,
+                chord_sizes: Vec::new(),
+                grace_note_count: 0,
+                tuplets: TupletUsage::default(),
+                tempo: Tempo::quarter_notes(120.0),
                 time_signature: TimeSignature::new(3, 4),
+                length_quarters: 3.0,
                 pitches: HashSet::new(),
+                sounding_pitches: HashSet::new(),
+                percussion_instruments: HashSet::new(),
             },
         ];
 
@@ -163,7 +239,54 @@ mod tests {
         // Peak: 0.667 notes/sec (second measure)
         assert_float_absolute_eq!(metrics.average_notes_per_second, 2.0 / 3.5);
         assert_float_absolute_eq!(metrics.peak_notes_per_second, 1.0 / 1.5);
-        assert_eq!(metrics.peak_measure, 2);
+        assert_eq!(metrics.peak_measure.as_deref(), Some("2"));
         assert_eq!(metrics.total_note_count, 2);
     }
+
+    #[test]
+    fn test_calculate_density_metrics_counts_chords_as_one_onset() {
+        // Arrange
+        let measure_data = vec![
+            MeasureData {
+                number: "1".to_string(),
+                note_count: 8,
+                onset_count: 8,
+                chord_sizes: Vec::new(),
+                grace_note_count: 0,
+                tuplets: TupletUsage::default(),
+                tempo: Tempo::quarter_notes(120.0),
+                time_signature: TimeSignature::new(4, 4),
+                length_quarters: 4.0,
+                pitches: HashSet::new(),
+                sounding_pitches: HashSet::new(),
+                percussion_instruments: HashSet::new(),
+            },
+            MeasureData {
+                number: "2".to_string(),
+                note_count: 8,
+                onset_count: 2,
+                chord_sizes: vec![3, 5],
+                grace_note_count: 0,
+                tuplets: TupletUsage::default(),
+                tempo: Tempo::quarter_notes(120.0),
+                time_signature: TimeSignature::new(4, 4),
+                length_quarters: 4.0,
+                pitches: HashSet::new(),
+                sounding_pitches: HashSet::new(),
+                percussion_instruments: HashSet::new(),
+            },
+        ];
+
+        // Act
+        let metrics = calculate_density_metrics(&measure_data);
+
+        // Assert
+        // Both measures have 8 notes in 2 seconds, but the second one only 2 attacks
+        assert_float_absolute_eq!(metrics.peak_notes_per_second, 4.0);
+        assert_float_absolute_eq!(metrics.average_onsets_per_second, 10.0 / 4.0);
+        assert_float_absolute_eq!(metrics.peak_onsets_per_second, 4.0);
+        assert_eq!(metrics.total_onset_count, 10);
+        assert_float_absolute_eq!(metrics.average_chord_size, 4.0);
+        assert_eq!(metrics.max_chord_size, 5);
+    }
 }
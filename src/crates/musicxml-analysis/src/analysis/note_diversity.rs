@@ -1,32 +1,45 @@
 use std::collections::HashSet;
 
-use crate::model::{MeasureData, PianoKey, Pitch};
+use crate::model::{MeasureData, PercussionInstrument, PianoKey, Pitch};
 
 use super::DiversityMetrics;
 
 pub fn calculate_diversity_metrics(measure_data: &[MeasureData]) -> DiversityMetrics {
     let mut all_pitches: HashSet<Pitch> = HashSet::new();
+    let mut all_sounding_pitches: HashSet<Pitch> = HashSet::new();
+    let mut all_percussion_instruments: HashSet<&PercussionInstrument> = HashSet::new();
 
     for data in measure_data {
         all_pitches.extend(&data.pitches);
+        all_sounding_pitches.extend(&data.sounding_pitches);
+        all_percussion_instruments.extend(&data.percussion_instruments);
     }
 
-    let mut all_keys: HashSet<PianoKey> = HashSet::new();
-    for pitch in &all_pitches {
-        let key = PianoKey::from_pitch(pitch);
-        all_keys.insert(key);
-    }
+    let microtonal_pitch_count = all_pitches
+        .iter()
+        .filter(|pitch| pitch.accidental.is_microtonal())
+        .count() as u32;
+    // Pitches count as written, keys as played, so a transposing part is on the
+    // keys it sounds on
+    let all_keys: HashSet<PianoKey> = all_sounding_pitches
+        .iter()
+        .filter_map(PianoKey::from_pitch)
+        .collect();
 
     DiversityMetrics {
         total_unique_pitches: all_pitches.len() as u32,
         total_unique_keys: all_keys.len() as u32,
+        total_unique_microtonal_pitches: microtonal_pitch_count,
+        total_unique_percussion_instruments: all_percussion_instruments.len() as u32,
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::model::{Accidental, MeasureData, NoteName, Pitch, TimeSignature};
+    use crate::model::{
+        Accidental, MeasureData, NoteName, Pitch, Tempo, TimeSignature, TupletUsage,
+    };
 
     #[test]
     fn test_calculate_diversity_metrics_empty_data() {
@@ -40,6 +53,8 @@ mod tests {
         let expected = DiversityMetrics {
             total_unique_pitches: 0,
             total_unique_keys: 0,
+            total_unique_microtonal_pitches: 0,
+            total_unique_percussion_instruments: 0,
         };
         assert_eq!(actual, expected);
     }
@@ -54,10 +69,18 @@ mod tests {
         ]);
 
         let measure_data = vec![MeasureData {
+            number: "1".to_string(),
             note_count: 3,
+            onset_count: 3,
+            chord_sizes: Vec::new(),
+            grace_note_count: 0,
+            tuplets: TupletUsage::default(),
+            sounding_pitches: pitches.clone(),
             pitches,
-            tempo_bpm: 120.0,
+            percussion_instruments: HashSet::new(),
+            tempo: Tempo::quarter_notes(120.0),
             time_signature: TimeSignature::new(4, 4),
+            length_quarters: 4.0,
         }];
 
         // Act
@@ -67,6 +90,8 @@ mod tests {
         let expected = DiversityMetrics {
             total_unique_pitches: 3,
             total_unique_keys: 3,
+            total_unique_microtonal_pitches: 0,
+            total_unique_percussion_instruments: 0,
         };
         assert_eq!(actual, expected);
     }
@@ -78,16 +103,32 @@ mod tests {
 
         let measure_data = vec![
             MeasureData {
+                number: "1".to_string(),
                 note_count: 1,
+                onset_count: 1,
+                chord_sizes: Vec::new(),
+                grace_note_count: 0,
+                tuplets: TupletUsage::default(),
                 pitches: HashSet::from([c4_pitch]),
-                tempo_bpm: 120.0,
+                sounding_pitches: HashSet::from([c4_pitch]),
+                percussion_instruments: HashSet::new(),
+                tempo: Tempo::quarter_notes(120.0),
                 time_signature: TimeSignature::new(4, 4),
+                length_quarters: 4.0,
             },
             MeasureData {
+                number: "2".to_string(),
                 note_count: 1,
+                onset_count: 1,
+                chord_sizes: Vec::new(),
+                grace_note_count: 0,
+                tuplets: TupletUsage::default(),
                 pitches: HashSet::from([c4_pitch]),
-                tempo_bpm: 120.0,
+                sounding_pitches: HashSet::from([c4_pitch]),
+                percussion_instruments: HashSet::new(),
+                tempo: Tempo::quarter_notes(120.0),
                 time_signature: TimeSignature::new(4, 4),
+                length_quarters: 4.0,
             },
         ];
 
@@ -98,6 +139,8 @@ mod tests {
         let expected = DiversityMetrics {
             total_unique_pitches: 1,
             total_unique_keys: 1,
+            total_unique_microtonal_pitches: 0,
+            total_unique_percussion_instruments: 0,
         };
         assert_eq!(actual, expected);
     }
@@ -110,16 +153,32 @@ mod tests {
 
         let measure_data = vec![
             MeasureData {
+                number: "1".to_string(),
                 note_count: 1,
+                onset_count: 1,
+                chord_sizes: Vec::new(),
+                grace_note_count: 0,
+                tuplets: TupletUsage::default(),
                 pitches: HashSet::from([c4_sharp_pitch]),
-                tempo_bpm: 120.0,
+                sounding_pitches: HashSet::from([c4_sharp_pitch]),
+                percussion_instruments: HashSet::new(),
+                tempo: Tempo::quarter_notes(120.0),
                 time_signature: TimeSignature::new(4, 4),
+                length_quarters: 4.0,
             },
             MeasureData {
+                number: "2".to_string(),
                 note_count: 1,
+                onset_count: 1,
+                chord_sizes: Vec::new(),
+                grace_note_count: 0,
+                tuplets: TupletUsage::default(),
                 pitches: HashSet::from([d4_flat_pitch]),
-                tempo_bpm: 120.0,
+                sounding_pitches: HashSet::from([d4_flat_pitch]),
+                percussion_instruments: HashSet::new(),
+                tempo: Tempo::quarter_notes(120.0),
                 time_signature: TimeSignature::new(4, 4),
+                length_quarters: 4.0,
             },
         ];
 
@@ -130,7 +189,92 @@ mod tests {
         let expected = DiversityMetrics {
             total_unique_pitches: 2,
             total_unique_keys: 1,
+            total_unique_microtonal_pitches: 0,
+            total_unique_percussion_instruments: 0,
+        };
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_calculate_diversity_metrics_counts_microtonal_pitches_without_keys() {
+        // Arrange
+        let pitches = HashSet::from([
+            Pitch::new(NoteName::E, 4, Accidental::Natural),
+            Pitch::new(NoteName::E, 4, Accidental::QuarterFlat),
+            Pitch::new(NoteName::E, 4, Accidental::Flat),
+            Pitch::new(NoteName::F, 4, Accidental::ThreeQuarterSharp),
+        ]);
+        let measure_data = vec![MeasureData {
+            number: "1".to_string(),
+            note_count: 4,
+            onset_count: 4,
+            chord_sizes: Vec::new(),
+            grace_note_count: 0,
+            tuplets: TupletUsage::default(),
+            sounding_pitches: pitches.clone(),
+            pitches,
+            percussion_instruments: HashSet::new(),
+            tempo: Tempo::quarter_notes(120.0),
+            time_signature: TimeSignature::new(4, 4),
+            length_quarters: 4.0,
+        }];
+
+        // Act
+        let actual = calculate_diversity_metrics(&measure_data);
+
+        // Assert
+        let expected = DiversityMetrics {
+            total_unique_pitches: 4,
+            total_unique_keys: 2,
+            total_unique_microtonal_pitches: 2,
+            total_unique_percussion_instruments: 0,
         };
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn test_calculate_diversity_metrics_counts_percussion_instruments_apart() {
+        // Arrange
+        let snare = PercussionInstrument::Instrument("P1-I39".to_string());
+        let hi_hat = PercussionInstrument::Instrument("P1-I43".to_string());
+        let kick = PercussionInstrument::StaffPosition(
+            "P1".to_string(),
+            Pitch::new(NoteName::F, 4, Accidental::Natural),
+        );
+        let measure_data = vec![
+            create_percussion_measure_data(HashSet::from([snare.clone(), hi_hat])),
+            create_percussion_measure_data(HashSet::from([snare, kick])),
+        ];
+
+        // Act
+        let actual = calculate_diversity_metrics(&measure_data);
+
+        // Assert
+        let expected = DiversityMetrics {
+            total_unique_pitches: 0,
+            total_unique_keys: 0,
+            total_unique_microtonal_pitches: 0,
+            total_unique_percussion_instruments: 3,
+        };
+        assert_eq!(actual, expected);
+    }
+
+    fn create_percussion_measure_data(
+        percussion_instruments: HashSet<PercussionInstrument>,
+    ) -> MeasureData {
+        MeasureData {
+            number: "1".to_string(),
+            note_count: percussion_instruments.len() as u32,
+            onset_count: 1,
+            chord_sizes: Vec::new(),
+            grace_note_count: 0,
+            tuplets: TupletUsage::default(),
+            tempo: Tempo::quarter_notes(120.0),
+            time_signature: TimeSignature::new(4, 4),
+            length_quarters: 4.0,
+            pitches: HashSet::new(),
+            sounding_pitches: HashSet::new(),
+            percussion_instruments,
+        }
+    }
 }
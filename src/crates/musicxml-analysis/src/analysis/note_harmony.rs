@@ -0,0 +1,267 @@
+use std::collections::{BTreeSet, HashSet};
+
+use super::HarmonicMetrics;
+use crate::model::{Chord, ChromaticNoteName, NoteKind, PianoKey, RationalTime, Timeline};
+
+pub fn calculate_harmonic_metrics(timeline: &Timeline) -> HarmonicMetrics {
+    let chords: Vec<Chord> = vertical_sonorities(timeline)
+        .iter()
+        .filter_map(|sonority| Chord::from_pitch_classes(&sonority.pitch_classes, sonority.bass))
+        .collect();
+
+    let vocabulary: HashSet<&Chord> = chords.iter().collect();
+    let chord_change_count = chords.windows(2).filter(|pair| pair[0] != pair[1]).count();
+    let unclassified_count = chords.iter().filter(|chord| !chord.is_tertian()).count();
+    let total_duration_seconds: f64 = timeline
+        .measures
+        .iter()
+        .map(|measure| measure.duration_seconds())
+        .sum();
+
+    let chord_changes_per_second = if total_duration_seconds > 0.0 {
+        chord_change_count as f64 / total_duration_seconds
+    } else {
+        0.0
+    };
+    let non_tertian_share = if chords.is_empty() {
+        0.0
+    } else {
+        unclassified_count as f64 / chords.len() as f64
+    };
+
+    HarmonicMetrics {
+        sonority_count: chords.len() as u32,
+        chord_vocabulary_size: vocabulary.len() as u32,
+        chord_changes_per_second,
+        non_tertian_share,
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Sonority {
+    pitch_classes: BTreeSet<ChromaticNoteName>,
+    bass: ChromaticNoteName,
+}
+
+struct SoundingNote {
+    start: RationalTime,
+    end: RationalTime,
+    key: PianoKey,
+}
+
+// The notes sounding between each pair of consecutive note starts and ends, in
+// concert pitch and across all parts. Grace notes, cue notes, unpitched and
+// microtonal notes take no part in the harmony.
+fn vertical_sonorities(timeline: &Timeline) -> Vec<Sonority> {
+    let mut notes: Vec<SoundingNote> = timeline
+        .notes
+        .iter()
+        .filter(|note| note.kind == NoteKind::Regular && !note.is_unpitched)
+        .filter_map(|note| {
+            let start = timeline.measures[note.measure_index].start + note.onset;
+            Some(SoundingNote {
+                start,
+                end: start + note.duration,
+                key: PianoKey::from_pitch(&note.sounding_pitch)?,
+            })
+        })
+        .filter(|note| note.end > note.start)
+        .collect();
+    notes.sort_by_key(|note| note.start);
+
+    let mut boundaries: Vec<RationalTime> = notes
+        .iter()
+        .flat_map(|note| [note.start, note.end])
+        .collect();
+    boundaries.sort();
+    boundaries.dedup();
+
+    let mut sonorities: Vec<Sonority> = Vec::new();
+    let mut sounding: Vec<&SoundingNote> = Vec::new();
+    let mut next_note = 0;
+    for boundary in boundaries {
+        sounding.retain(|note| note.end > boundary);
+        while next_note < notes.len() && notes[next_note].start == boundary {
+            sounding.push(&notes[next_note]);
+            next_note += 1;
+        }
+
+        let Some(bass) = sounding.iter().map(|note| note.key).min() else {
+            continue;
+        };
+        let sonority = Sonority {
+            pitch_classes: sounding.iter().map(|note| note.key.note_name).collect(),
+            bass: bass.note_name,
+        };
+        // A voice moving within the same sonority does not start a new one
+        if sonorities.last() != Some(&sonority) {
+            sonorities.push(sonority);
+        }
+    }
+    sonorities
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_float_eq::assert_float_absolute_eq;
+
+    use super::*;
+    use crate::model::{
+        Accidental, MeasureFill, NoteEvent, NoteName, PartMeasure, Pitch, Tempo, TimeSignature,
+        TimelineMeasure,
+    };
+
+    #[test]
+    fn test_calculate_harmonic_metrics_empty_timeline() {
+        // Arrange
+        let timeline = Timeline::default();
+
+        // Act
+        let metrics = calculate_harmonic_metrics(&timeline);
+
+        // Assert
+        assert_eq!(metrics.sonority_count, 0);
+        assert_eq!(metrics.chord_vocabulary_size, 0);
+        assert_float_absolute_eq!(metrics.chord_changes_per_second, 0.0);
+        assert_float_absolute_eq!(metrics.non_tertian_share, 0.0);
+    }
+
+    #[test]
+    fn test_calculate_harmonic_metrics_counts_chord_changes() {
+        // Arrange: C major, G major, C major, one half note each, at 120 BPM
+        let c_major = [(NoteName::C, 4), (NoteName::E, 4), (NoteName::G, 4)];
+        let g_major = [(NoteName::G, 3), (NoteName::B, 3), (NoteName::D, 4)];
+        let mut notes = create_block_chord(&c_major, 0, 2);
+        notes.extend(create_block_chord(&g_major, 2, 2));
+        notes.extend(create_block_chord(&c_major, 4, 2));
+        let timeline = create_timeline(2, notes);
+
+        // Act
+        let metrics = calculate_harmonic_metrics(&timeline);
+
+        // Assert
+        assert_eq!(metrics.sonority_count, 3);
+        assert_eq!(metrics.chord_vocabulary_size, 2);
+        assert_float_absolute_eq!(metrics.chord_changes_per_second, 2.0 / 4.0);
+        assert_float_absolute_eq!(metrics.non_tertian_share, 0.0);
+    }
+
+    #[test]
+    fn test_calculate_harmonic_metrics_combines_sustained_and_moving_notes() {
+        // Arrange: a whole note bass C under E and G, then A and D in quarters
+        let mut notes = create_block_chord(&[(NoteName::C, 3)], 0, 4);
+        notes.extend(create_block_chord(&[(NoteName::E, 4)], 0, 1));
+        notes.extend(create_block_chord(&[(NoteName::G, 4)], 1, 1));
+        notes.extend(create_block_chord(
+            &[(NoteName::A, 4), (NoteName::E, 4)],
+            2,
+            1,
+        ));
+        notes.extend(create_block_chord(
+            &[(NoteName::D, 4), (NoteName::F, 4)],
+            3,
+            1,
+        ));
+        let timeline = create_timeline(1, notes);
+
+        // Act
+        let metrics = calculate_harmonic_metrics(&timeline);
+
+        // Assert: C-E and C-G are dyads, C-E-A is A minor over C, C-D-F is unclassified
+        assert_eq!(metrics.sonority_count, 2);
+        assert_eq!(metrics.chord_vocabulary_size, 2);
+        assert_float_absolute_eq!(metrics.chord_changes_per_second, 1.0 / 2.0);
+        assert_float_absolute_eq!(metrics.non_tertian_share, 0.5);
+    }
+
+    #[test]
+    fn test_calculate_harmonic_metrics_merges_repeated_sonorities() {
+        // Arrange: the same C major chord struck four times
+        let c_major = [(NoteName::C, 4), (NoteName::E, 4), (NoteName::G, 4)];
+        let notes = (0..4)
+            .flat_map(|onset| create_block_chord(&c_major, onset, 1))
+            .collect();
+        let timeline = create_timeline(1, notes);
+
+        // Act
+        let metrics = calculate_harmonic_metrics(&timeline);
+
+        // Assert
+        assert_eq!(metrics.sonority_count, 1);
+        assert_eq!(metrics.chord_vocabulary_size, 1);
+        assert_float_absolute_eq!(metrics.chord_changes_per_second, 0.0);
+    }
+
+    #[test]
+    fn test_calculate_harmonic_metrics_ignores_grace_notes() {
+        // Arrange
+        let mut notes = create_block_chord(&[(NoteName::C, 4), (NoteName::E, 4)], 0, 4);
+        let mut grace_notes = create_block_chord(&[(NoteName::G, 4)], 0, 1);
+        grace_notes[0].kind = NoteKind::Grace(Default::default());
+        notes.extend(grace_notes);
+        let timeline = create_timeline(1, notes);
+
+        // Act
+        let metrics = calculate_harmonic_metrics(&timeline);
+
+        // Assert
+        assert_eq!(metrics.sonority_count, 0);
+    }
+
+    // Notes in quarters from the start of the first measure, all in 4/4
+    fn create_block_chord(
+        pitches: &[(NoteName, u8)],
+        onset_quarters: i64,
+        duration_quarters: i64,
+    ) -> Vec<NoteEvent> {
+        pitches
+            .iter()
+            .map(|(note_name, octave)| {
+                let pitch = Pitch::new(*note_name, *octave, Accidental::Natural);
+                NoteEvent {
+                    part_id: "P1".to_string(),
+                    measure_index: (onset_quarters / 4) as usize,
+                    voice: None,
+                    staff: 1,
+                    written_pitch: pitch,
+                    sounding_pitch: pitch,
+                    doubled_pitch: None,
+                    is_unpitched: false,
+                    instrument_id: None,
+                    onset: RationalTime::from_quarters(onset_quarters % 4),
+                    duration: RationalTime::from_quarters(duration_quarters),
+                    onset_seconds: onset_quarters as f64 * 0.5,
+                    duration_seconds: duration_quarters as f64 * 0.5,
+                    is_chord_member: pitches.len() > 1,
+                    kind: NoteKind::Regular,
+                    tuplets: Vec::new(),
+                }
+            })
+            .collect()
+    }
+
+    fn create_timeline(measure_count: i64, notes: Vec<NoteEvent>) -> Timeline {
+        let measures = (0..measure_count)
+            .map(|index| TimelineMeasure {
+                number: (index + 1).to_string(),
+                tempo: Tempo::quarter_notes(120.0),
+                time_signature: TimeSignature::new(4, 4),
+                start: RationalTime::from_quarters(index * 4),
+                length: RationalTime::from_quarters(4),
+                start_seconds: index as f64 * 2.0,
+                implicit: false,
+                parts: vec![PartMeasure {
+                    part_id: "P1".to_string(),
+                    fill: MeasureFill::Complete,
+                    key_changes: Vec::new(),
+                    clef_changes: Vec::new(),
+                }],
+            })
+            .collect();
+        Timeline {
+            measures,
+            notes,
+            ..Default::default()
+        }
+    }
+}
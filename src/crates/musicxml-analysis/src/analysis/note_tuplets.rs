@@ -0,0 +1,120 @@
+use std::collections::HashSet;
+
+use super::TupletMetrics;
+use crate::model::{MeasureData, TupletRatio};
+
+pub fn calculate_tuplet_metrics(measure_data: &[MeasureData]) -> TupletMetrics {
+    let total_note_count: u32 = measure_data.iter().map(|data| data.note_count).sum();
+    let tuplet_note_count: u32 = measure_data
+        .iter()
+        .map(|data| data.tuplets.note_count)
+        .sum();
+    let nested_tuplet_note_count = measure_data
+        .iter()
+        .map(|data| data.tuplets.nested_note_count)
+        .sum();
+    let cross_rhythm_measures = measure_data
+        .iter()
+        .filter(|data| data.tuplets.cross_rhythm)
+        .count() as u32;
+
+    let tuplet_ratios: HashSet<TupletRatio> = measure_data
+        .iter()
+        .flat_map(|data| data.tuplets.ratios.iter().copied())
+        .collect();
+    let mut tuplet_ratios: Vec<TupletRatio> = tuplet_ratios.into_iter().collect();
+    tuplet_ratios.sort_unstable();
+
+    let tuplet_note_share = if total_note_count > 0 {
+        tuplet_note_count as f64 / total_note_count as f64
+    } else {
+        0.0
+    };
+
+    TupletMetrics {
+        tuplet_note_count,
+        tuplet_note_share,
+        nested_tuplet_note_count,
+        tuplet_ratios,
+        cross_rhythm_measures,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_float_eq::assert_float_absolute_eq;
+
+    use crate::model::{Tempo, TimeSignature, TupletUsage};
+
+    use super::*;
+
+    #[test]
+    fn test_calculate_tuplet_metrics_empty_data() {
+        // Arrange
+        let measure_data = vec![];
+
+        // Act
+        let metrics = calculate_tuplet_metrics(&measure_data);
+
+        // Assert
+        assert_eq!(metrics.tuplet_note_count, 0);
+        assert_float_absolute_eq!(metrics.tuplet_note_share, 0.0);
+        assert!(metrics.tuplet_ratios.is_empty());
+        assert_eq!(metrics.cross_rhythm_measures, 0);
+    }
+
+    #[test]
+    fn test_calculate_tuplet_metrics_combines_measures() {
+        // Arrange
+        let triplet = TupletRatio::new(3, 2);
+        let quintuplet = TupletRatio::new(5, 4);
+        let measure_data = vec![
+            create_measure_data(
+                8,
+                TupletUsage {
+                    note_count: 6,
+                    nested_note_count: 0,
+                    ratios: HashSet::from([triplet]),
+                    cross_rhythm: true,
+                },
+            ),
+            create_measure_data(
+                8,
+                TupletUsage {
+                    note_count: 5,
+                    nested_note_count: 5,
+                    ratios: HashSet::from([triplet, quintuplet]),
+                    cross_rhythm: false,
+                },
+            ),
+            create_measure_data(6, TupletUsage::default()),
+        ];
+
+        // Act
+        let metrics = calculate_tuplet_metrics(&measure_data);
+
+        // Assert
+        assert_eq!(metrics.tuplet_note_count, 11);
+        assert_float_absolute_eq!(metrics.tuplet_note_share, 0.5);
+        assert_eq!(metrics.nested_tuplet_note_count, 5);
+        assert_eq!(metrics.tuplet_ratios, vec![triplet, quintuplet]);
+        assert_eq!(metrics.cross_rhythm_measures, 1);
+    }
+
+    fn create_measure_data(note_count: u32, tuplets: TupletUsage) -> MeasureData {
+        MeasureData {
+            number: "1".to_string(),
+            note_count,
+            onset_count: note_count,
+            chord_sizes: Vec::new(),
+            grace_note_count: 0,
+            tuplets,
+            tempo: Tempo::quarter_notes(120.0),
+            time_signature: TimeSignature::new(4, 4),
+            length_quarters: 4.0,
+            pitches: HashSet::new(),
+            sounding_pitches: HashSet::new(),
+            percussion_instruments: HashSet::new(),
+        }
+    }
+}
@@ -0,0 +1,10 @@
+use crate::model::TupletRatio;
+
+#[derive(Debug, PartialEq)]
+pub struct TupletMetrics {
+    pub tuplet_note_count: u32,
+    pub tuplet_note_share: f64, // of all notes, 0.0 to 1.0
+    pub nested_tuplet_note_count: u32,
+    pub tuplet_ratios: Vec<TupletRatio>, // every ratio used, sorted
+    pub cross_rhythm_measures: u32,
+}
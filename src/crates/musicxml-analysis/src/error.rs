@@ -0,0 +1,74 @@
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MeasureLocation {
+    pub part_id: String,
+    pub measure_number: String, // as written in the score, not the index
+}
+
+impl MeasureLocation {
+    pub fn new(part_id: &str, measure_number: &str) -> Self {
+        Self {
+            part_id: part_id.to_string(),
+            measure_number: measure_number.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for MeasureLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "measure {} of part {}",
+            self.measure_number, self.part_id
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AnalysisError {
+    UnsupportedMetronome(MeasureLocation),
+    UnsupportedAlter(MeasureLocation, i32), // in cents
+}
+
+impl AnalysisError {
+    pub fn location(&self) -> &MeasureLocation {
+        match self {
+            AnalysisError::UnsupportedMetronome(location)
+            | AnalysisError::UnsupportedAlter(location, _) => location,
+        }
+    }
+}
+
+impl fmt::Display for AnalysisError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnalysisError::UnsupportedMetronome(location) => {
+                write!(f, "unsupported metronome mark in {location}")
+            }
+            AnalysisError::UnsupportedAlter(location, cents) => {
+                let semitones = *cents as f64 / 100.0;
+                write!(f, "unsupported alter {semitones} in {location}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AnalysisError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analysis_error_display_includes_location() {
+        // Arrange
+        let sut = AnalysisError::UnsupportedAlter(MeasureLocation::new("P2", "12"), 400);
+
+        // Act
+        let actual = sut.to_string();
+
+        // Assert
+        assert_eq!(actual, "unsupported alter 4 in measure 12 of part P2");
+    }
+}
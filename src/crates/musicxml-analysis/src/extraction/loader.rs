@@ -0,0 +1,281 @@
+use std::{
+    fs,
+    io::{Cursor, Read},
+    path::Path,
+};
+
+use musicxml::elements::{PartListElement, ScorePartwise};
+use zip::ZipArchive;
+
+use crate::extraction::microtonal_alters::{MicrotonalAlters, round_microtonal_alters};
+
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+const CONTAINER_PATH: &str = "META-INF/container.xml";
+
+// The score as the musicxml crate reads it, with the fractional alters it
+// cannot hold rounded, next to their real values
+#[derive(Debug)]
+pub struct LoadedScore {
+    pub score: ScorePartwise,
+    pub microtonal_alters: MicrotonalAlters,
+}
+
+pub fn load_score(path: &str) -> Result<LoadedScore, String> {
+    let data = fs::read(Path::new(path)).map_err(|e| format!("Cannot read {path}: {e}"))?;
+    load_score_from_bytes(data)
+}
+
+pub fn load_score_from_bytes(data: Vec<u8>) -> Result<LoadedScore, String> {
+    if data.starts_with(ZIP_MAGIC) {
+        read_mxl_score(&data)
+    } else {
+        parse_score_data(data)
+    }
+}
+
+// Accepts <score-timewise> too, the musicxml crate converts it to partwise
+fn parse_score_data(data: Vec<u8>) -> Result<LoadedScore, String> {
+    let (data, microtonal_alters) = match std::str::from_utf8(&data)
+        .ok()
+        .and_then(round_microtonal_alters)
+    {
+        Some((xml, microtonal_alters)) => (xml.into_bytes(), microtonal_alters),
+        None => (data, MicrotonalAlters::default()),
+    };
+    let mut score = musicxml::read_score_data_partwise(data)?;
+    order_parts_by_part_list(&mut score);
+    Ok(LoadedScore {
+        score,
+        microtonal_alters,
+    })
+}
+
+// Converted timewise scores list their parts by id instead of in score order
+fn order_parts_by_part_list(score: &mut ScorePartwise) {
+    let part_ids: Vec<&str> = score
+        .content
+        .part_list
+        .content
+        .content
+        .iter()
+        .filter_map(|element| match element {
+            PartListElement::ScorePart(score_part) => Some(score_part.attributes.id.as_str()),
+            PartListElement::PartGroup(_) => None,
+        })
+        .collect();
+    let position = |id: &str| part_ids.iter().position(|part_id| *part_id == id);
+    let mut parts = std::mem::take(&mut score.content.part);
+    parts.sort_by_key(|part| position(&part.attributes.id).unwrap_or(usize::MAX));
+    score.content.part = parts;
+}
+
+// The musicxml crate reads .mxl too, but fails on stored entries and on entries
+// written with data descriptors, as streaming zip writers produce them; see
+// test_musicxml_crate_cannot_read_mxl. It also keeps the XML text to itself,
+// which round_microtonal_alters needs.
+pub fn read_mxl_score(data: &[u8]) -> Result<LoadedScore, String> {
+    let mut archive =
+        ZipArchive::new(Cursor::new(data)).map_err(|e| format!("Not a zip archive: {e}"))?;
+    let root_path = match read_archive_file(&mut archive, CONTAINER_PATH) {
+        Ok(container) => find_root_file_path(&container)?,
+        Err(_) => archive
+            .file_names()
+            .find(|name| is_score_file_name(name))
+            .ok_or_else(|| format!("Archive has no {CONTAINER_PATH} and no score file"))?
+            .to_string(),
+    };
+
+    let root_file = read_archive_file(&mut archive, &root_path)
+        .map_err(|_| format!("Root file {root_path} not found in archive"))?;
+    parse_score_data(root_file)
+}
+
+fn read_archive_file(
+    archive: &mut ZipArchive<Cursor<&[u8]>>,
+    name: &str,
+) -> Result<Vec<u8>, String> {
+    let mut file = archive
+        .by_name(name)
+        .map_err(|e| format!("Cannot open {name}: {e}"))?;
+    let mut content = Vec::new();
+    file.read_to_end(&mut content)
+        .map_err(|e| format!("Cannot read {name}: {e}"))?;
+    Ok(content)
+}
+
+fn is_score_file_name(name: &str) -> bool {
+    !name.starts_with("META-INF/") && (name.ends_with(".musicxml") || name.ends_with(".xml"))
+}
+
+// The first <rootfile> is the main score; later ones are alternative renditions
+fn find_root_file_path(container: &[u8]) -> Result<String, String> {
+    let container =
+        std::str::from_utf8(container).map_err(|e| format!("Cannot read {CONTAINER_PATH}: {e}"))?;
+    let document = roxmltree::Document::parse(container)
+        .map_err(|e| format!("Cannot parse {CONTAINER_PATH}: {e}"))?;
+    document
+        .descendants()
+        .find(|node| node.has_tag_name("rootfile"))
+        .and_then(|rootfile| rootfile.attribute("full-path"))
+        .map(str::to_string)
+        .ok_or_else(|| format!("No rootfile listed in {CONTAINER_PATH}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        analysis::{calculate_density_metrics, calculate_diversity_metrics},
+        extraction::musicxml::extract_measure_data,
+    };
+    use rstest::rstest;
+    use std::io::Write;
+    use zip::{ZipWriter, write::SimpleFileOptions};
+
+    const TEST_FILES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../../test-files");
+
+    #[rstest]
+    #[case("hot_cross_buns", "hot_cross_buns")] // deflated, root file at the top level
+    #[case("minimal", "minimal")] // stored, root file in a subdirectory
+    #[case("hot_cross_buns_streamed", "hot_cross_buns")] // deflated with data descriptors
+    fn test_load_score_mxl_matches_uncompressed(#[case] name: &str, #[case] uncompressed: &str) {
+        // Arrange
+        let expected_score = load_score(&format!("{TEST_FILES}/{uncompressed}.musicxml")).unwrap();
+
+        // Act
+        let actual_score = load_score(&format!("{TEST_FILES}/{name}.mxl")).unwrap();
+
+        // Assert
+        assert_eq!(
+            extract_measure_data(&actual_score.score).unwrap(),
+            extract_measure_data(&expected_score.score).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_load_score_timewise_matches_partwise() {
+        // Arrange
+        let expected_score = load_score(&format!("{TEST_FILES}/hot_cross_buns.musicxml")).unwrap();
+
+        // Act
+        let actual_score =
+            load_score(&format!("{TEST_FILES}/hot_cross_buns_timewise.musicxml")).unwrap();
+
+        // Assert
+        let actual = extract_measure_data(&actual_score.score).unwrap();
+        let expected = extract_measure_data(&expected_score.score).unwrap();
+        assert_eq!(actual, expected);
+        assert_eq!(
+            calculate_density_metrics(&actual),
+            calculate_density_metrics(&expected)
+        );
+        assert_eq!(
+            calculate_diversity_metrics(&actual),
+            calculate_diversity_metrics(&expected)
+        );
+    }
+
+    #[test]
+    fn test_load_score_timewise_keeps_part_list_order() {
+        // Arrange
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<score-timewise version="4.0">
+  <part-list>
+    <score-part id="P9"><part-name>Upper</part-name></score-part>
+    <score-part id="P10"><part-name>Lower</part-name></score-part>
+  </part-list>
+  <measure number="1">
+    <part id="P9"/>
+    <part id="P10"/>
+  </measure>
+</score-timewise>"#;
+
+        // Act
+        let actual = load_score_from_bytes(xml.as_bytes().to_vec()).unwrap();
+
+        // Assert
+        let part_ids: Vec<&str> = actual
+            .score
+            .content
+            .part
+            .iter()
+            .map(|part| part.attributes.id.as_str())
+            .collect();
+        assert_eq!(part_ids, vec!["P9", "P10"]);
+    }
+
+    #[test]
+    fn test_read_mxl_score_rejects_non_zip_data() {
+        // Arrange
+        let data = b"not a zip archive at all";
+
+        // Act
+        let actual = read_mxl_score(data);
+
+        // Assert
+        assert!(actual.is_err());
+    }
+
+    #[test]
+    fn test_read_mxl_score_reports_missing_root_file() {
+        // Arrange
+        let container = r#"<container><rootfiles><rootfile full-path="missing.musicxml"/></rootfiles></container>"#;
+        let data = create_zip(&[(CONTAINER_PATH, container.as_bytes())]);
+
+        // Act
+        let actual = read_mxl_score(&data);
+
+        // Assert
+        assert_eq!(
+            actual.err(),
+            Some("Root file missing.musicxml not found in archive".to_string())
+        );
+    }
+
+    fn create_zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in files {
+            writer
+                .start_file(*name, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(content).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[rstest]
+    #[case(
+        r#"<container><rootfiles><rootfile full-path="score.musicxml"/></rootfiles></container>"#,
+        Ok("score.musicxml")
+    )]
+    #[case(
+        r#"<container><rootfiles><rootfile full-path="main.xml"/><rootfile full-path="alt.pdf"/></rootfiles></container>"#,
+        Ok("main.xml")
+    )]
+    #[case(
+        "<container><rootfiles></rootfiles></container>",
+        Err("No rootfile listed in META-INF/container.xml")
+    )]
+    fn test_find_root_file_path(#[case] container: &str, #[case] expected: Result<&str, &str>) {
+        // Act
+        let actual = find_root_file_path(container.as_bytes());
+
+        // Assert
+        assert_eq!(actual, expected.map(str::to_string).map_err(str::to_string));
+    }
+
+    #[rstest]
+    #[case("minimal")] // stored entries
+    #[case("hot_cross_buns_streamed")] // entries with data descriptors
+    fn test_musicxml_crate_cannot_read_mxl(#[case] name: &str) {
+        // Arrange
+        let data = fs::read(format!("{TEST_FILES}/{name}.mxl")).unwrap();
+
+        // Act
+        let actual = musicxml::read_score_data_partwise(data.clone());
+
+        // Assert
+        assert!(actual.is_err());
+        assert!(read_mxl_score(&data).is_ok());
+    }
+}
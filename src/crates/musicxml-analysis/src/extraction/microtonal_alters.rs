@@ -0,0 +1,278 @@
+use std::{collections::HashMap, ops::Range};
+
+use roxmltree::{Document, Node, ParsingOptions};
+
+// The musicxml crate reads <alter> and <key-alter> as whole semitones and drops
+// every note whose alter has a fraction, like the -0.5 of a quarter-tone flat.
+// Such alters are rounded in the XML text before parsing, so the notes survive,
+// and their real values are kept here, by where they sit in the score.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MicrotonalAlters {
+    measures: HashMap<MeasureKey, MeasureAlters>,
+}
+
+type MeasureKey = (String, String, usize); // part id, measure number, occurrence of that number
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MeasureAlters {
+    notes: HashMap<usize, i32>, // cents by index of the <note> in the measure
+    keys: HashMap<usize, i32>,  // cents of the first <key-alter> by index of the <key>
+}
+
+impl MicrotonalAlters {
+    // occurrence counts from 1 and tells apart measures that share a number
+    pub fn for_measure(
+        &self,
+        part_id: &str,
+        measure_number: &str,
+        occurrence: usize,
+    ) -> Option<&MeasureAlters> {
+        if self.measures.is_empty() {
+            return None;
+        }
+        self.measures
+            .get(&(part_id.to_string(), measure_number.to_string(), occurrence))
+    }
+}
+
+impl MeasureAlters {
+    pub fn note_cents(&self, note_index: usize) -> Option<i32> {
+        self.notes.get(&note_index).copied()
+    }
+
+    pub fn key_cents(&self, key_index: usize) -> Option<i32> {
+        self.keys.get(&key_index).copied()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.notes.is_empty() && self.keys.is_empty()
+    }
+}
+
+struct RoundedAlter {
+    range: Range<usize>, // of the value in the XML text
+    semitones: i16,
+    cents: i32,
+}
+
+// None when the score has no fractional alters, or is not XML that can be read
+pub fn round_microtonal_alters(xml: &str) -> Option<(String, MicrotonalAlters)> {
+    let options = ParsingOptions {
+        allow_dtd: true,
+        ..ParsingOptions::default()
+    };
+    let document = Document::parse_with_options(xml, options).ok()?;
+    let mut microtonal_alters = MicrotonalAlters::default();
+    let mut rounded_alters: Vec<RoundedAlter> = Vec::new();
+    let mut occurrences: HashMap<(&str, &str), usize> = HashMap::new();
+
+    for (part_id, measure_number, measure) in measures(document.root_element()) {
+        let occurrence = occurrences.entry((part_id, measure_number)).or_default();
+        *occurrence += 1;
+        let mut measure_alters = MeasureAlters::default();
+
+        for (note_index, note) in children(measure, "note").enumerate() {
+            if let Some(alter) = children(note, "pitch")
+                .flat_map(|pitch| children(pitch, "alter"))
+                .find_map(round_alter)
+            {
+                measure_alters.notes.insert(note_index, alter.cents);
+                rounded_alters.push(alter);
+            }
+        }
+
+        let keys =
+            children(measure, "attributes").flat_map(|attributes| children(attributes, "key"));
+        for (key_index, key) in keys.enumerate() {
+            if let Some(alter) = children(key, "key-alter").next().and_then(round_alter) {
+                measure_alters.keys.insert(key_index, alter.cents);
+                rounded_alters.push(alter);
+            }
+        }
+
+        if !measure_alters.is_empty() {
+            microtonal_alters.measures.insert(
+                (part_id.to_string(), measure_number.to_string(), *occurrence),
+                measure_alters,
+            );
+        }
+    }
+
+    if rounded_alters.is_empty() {
+        return None;
+    }
+
+    rounded_alters.sort_by_key(|alter| alter.range.start);
+    let mut rounded = String::with_capacity(xml.len());
+    let mut copied_up_to = 0;
+    for alter in &rounded_alters {
+        rounded.push_str(&xml[copied_up_to..alter.range.start]);
+        rounded.push_str(&alter.semitones.to_string());
+        copied_up_to = alter.range.end;
+    }
+    rounded.push_str(&xml[copied_up_to..]);
+    Some((rounded, microtonal_alters))
+}
+
+// The content of every part in every measure, for partwise and timewise scores
+fn measures<'a, 'input>(root: Node<'a, 'input>) -> Vec<(&'a str, &'a str, Node<'a, 'input>)> {
+    let number = |measure: Node<'a, 'input>| measure.attribute("number").unwrap_or_default();
+    let id = |part: Node<'a, 'input>| part.attribute("id").unwrap_or_default();
+
+    if root.has_tag_name("score-timewise") {
+        children(root, "measure")
+            .flat_map(|measure| {
+                children(measure, "part").map(move |part| (id(part), number(measure), part))
+            })
+            .collect()
+    } else {
+        children(root, "part")
+            .flat_map(|part| {
+                children(part, "measure").map(move |measure| (id(part), number(measure), measure))
+            })
+            .collect()
+    }
+}
+
+fn children<'a, 'input>(
+    node: Node<'a, 'input>,
+    name: &'static str,
+) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.children()
+        .filter(move |child| child.has_tag_name(name))
+}
+
+// None for whole semitones, which the musicxml crate reads as they are
+fn round_alter(alter: Node) -> Option<RoundedAlter> {
+    let text = alter.first_child().filter(Node::is_text)?;
+    let value = text.text()?.trim();
+    if value.parse::<i16>().is_ok() {
+        return None;
+    }
+
+    let semitones: f64 = value.parse().ok()?;
+    Some(RoundedAlter {
+        range: text.range(),
+        semitones: semitones.round() as i16,
+        cents: (semitones * 100.0).round() as i32,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("-0.5", "-1", -50)]
+    #[case("1.5", "2", 150)]
+    #[case(" 0.25 ", "0", 25)]
+    #[case("-2.5", "-3", -250)]
+    fn test_round_microtonal_alters_of_note(
+        #[case] alter: &str,
+        #[case] expected_alter: &str,
+        #[case] expected_cents: i32,
+    ) {
+        // Arrange
+        let xml = create_partwise_xml(&format!(
+            r#"<measure number="1">{}</measure>"#,
+            create_note_with_alter(alter)
+        ));
+
+        // Act
+        let (rounded, actual) = round_microtonal_alters(&xml).unwrap();
+
+        // Assert
+        assert!(rounded.contains(&format!("<alter>{expected_alter}</alter>")));
+        let measure_alters = actual.for_measure("P1", "1", 1).unwrap();
+        assert_eq!(measure_alters.note_cents(0), Some(expected_cents));
+    }
+
+    fn create_partwise_xml(measures: &str) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE score-partwise PUBLIC "-//Recordare//DTD MusicXML 4.0 Partwise//EN" "http://www.musicxml.org/dtds/partwise.dtd">
+<score-partwise version="4.0">
+  <part-list><score-part id="P1"><part-name>Test</part-name></score-part></part-list>
+  <part id="P1">{measures}</part>
+</score-partwise>"#
+        )
+    }
+
+    fn create_note_with_alter(alter: &str) -> String {
+        format!(
+            "<note><pitch><step>E</step><alter>{alter}</alter><octave>4</octave></pitch><duration>1</duration></note>"
+        )
+    }
+
+    #[test]
+    fn test_round_microtonal_alters_keeps_whole_semitones() {
+        // Arrange
+        let xml = create_partwise_xml(&format!(
+            r#"<measure number="1">{}{}</measure>"#,
+            create_note_with_alter("-1"),
+            create_note_with_alter("2"),
+        ));
+
+        // Act
+        let actual = round_microtonal_alters(&xml);
+
+        // Assert
+        assert_eq!(actual, None);
+    }
+
+    #[test]
+    fn test_round_microtonal_alters_finds_note_and_key_by_position() {
+        // Arrange
+        let measures = format!(
+            r#"<measure number="1">
+  <attributes><key><fifths>0</fifths></key><key><key-step>B</key-step><key-alter>-0.5</key-alter></key></attributes>
+  {}{}
+</measure>
+<measure number="1">{}</measure>"#,
+            create_note_with_alter("-1"),
+            create_note_with_alter("0.5"),
+            create_note_with_alter("-1.5"),
+        );
+        let xml = create_partwise_xml(&measures);
+
+        // Act
+        let (_, actual) = round_microtonal_alters(&xml).unwrap();
+
+        // Assert
+        let first = actual.for_measure("P1", "1", 1).unwrap();
+        assert_eq!(first.key_cents(0), None);
+        assert_eq!(first.key_cents(1), Some(-50));
+        assert_eq!(first.note_cents(0), None);
+        assert_eq!(first.note_cents(1), Some(50));
+        let second = actual.for_measure("P1", "1", 2).unwrap();
+        assert_eq!(second.note_cents(0), Some(-150));
+    }
+
+    #[test]
+    fn test_round_microtonal_alters_of_timewise_score() {
+        // Arrange
+        let xml = format!(
+            r#"<score-timewise version="4.0">
+  <part-list>
+    <score-part id="P1"><part-name>Upper</part-name></score-part>
+    <score-part id="P2"><part-name>Lower</part-name></score-part>
+  </part-list>
+  <measure number="1"><part id="P1"/><part id="P2">{}</part></measure>
+</score-timewise>"#,
+            create_note_with_alter("0.5")
+        );
+
+        // Act
+        let (_, actual) = round_microtonal_alters(&xml).unwrap();
+
+        // Assert
+        assert_eq!(actual.for_measure("P1", "1", 1), None);
+        assert_eq!(
+            actual
+                .for_measure("P2", "1", 1)
+                .and_then(|measure_alters| measure_alters.note_cents(0)),
+            Some(50)
+        );
+    }
+}
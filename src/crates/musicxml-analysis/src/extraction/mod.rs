@@ -1 +1,6 @@
+pub mod loader;
+pub mod microtonal_alters;
 pub mod musicxml;
+pub mod options;
+pub mod performance_order;
+pub mod tempo_terms;
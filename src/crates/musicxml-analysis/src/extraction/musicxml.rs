@@ -1,120 +1,872 @@
-use std::collections::HashSet;
+use std::{cmp::Ordering, collections::HashMap};
 
 use musicxml::{
-    datatypes::NoteTypeValue,
+    datatypes::{Mode, NoteTypeValue, StartStop, StartStopContinue, Step, YesNo},
     elements::{
-        AudibleType, Measure, MeasureElement, MetronomeContents, NoteType, PartElement,
-        ScorePartwise,
+        AudibleType, BeatEquation, BeatUnit, BeatUnitDot, BeatUnitTied, Direction,
+        DirectionTypeContents, Grace, GraceType, Key, KeyContents, Measure, MeasureElement,
+        MetronomeContents, NotationContentTypes, Note, NoteType, PartElement, ScorePartwise, Sound,
+        Tie, Transpose, Tuplet, Unpitched,
     },
 };
 
-use crate::model::{MeasureData, NoteName, Pitch, TimeSignature, pitch::Accidental};
+use crate::{
+    error::{AnalysisError, MeasureLocation},
+    extraction::{
+        microtonal_alters::{MeasureAlters, MicrotonalAlters},
+        options::{ExtractionOptions, MeasureOrder, TempoRampOptions},
+        performance_order::{MeasureNavigation, performance_order},
+        tempo_terms::TempoTermTable,
+    },
+    model::{
+        BeatGrouping, Clef, ClefChange, ClefSign, GraceNote, KeyChange, KeyMode, KeySignature,
+        MeasureData, MeasureFill, NoteEvent, NoteKind, NoteName, PartMeasure, Pitch, RationalTime,
+        Tempo, TempoMap, TempoSource, TimeSignature, Timeline, TimelineMeasure, Transposition,
+        TupletRatio, pitch::Accidental,
+    },
+};
+
+const DEFAULT_DIVISIONS: u32 = 1;
+
+pub fn extract_measure_data(score: &ScorePartwise) -> Result<Vec<MeasureData>, AnalysisError> {
+    Ok(extract_timeline(score)?.to_measure_data())
+}
+
+pub fn extract_timeline(score: &ScorePartwise) -> Result<Timeline, AnalysisError> {
+    let (timeline, _) = extract_timeline_with_options(
+        score,
+        &MicrotonalAlters::default(),
+        &ExtractionOptions::default(),
+    )?;
+    Ok(timeline)
+}
+
+// Unsupported notation is recorded as a warning and replaced by a sensible default
+pub fn extract_timeline_lenient(score: &ScorePartwise) -> (Timeline, Vec<AnalysisError>) {
+    let options = ExtractionOptions {
+        lenient: true,
+        ..ExtractionOptions::default()
+    };
+    extract_timeline_with_options(score, &MicrotonalAlters::default(), &options)
+        .expect("lenient extraction never fails")
+}
+
+// microtonal_alters replace the rounded alters of a score from the loader
+pub fn extract_timeline_with_options(
+    score: &ScorePartwise,
+    microtonal_alters: &MicrotonalAlters,
+    options: &ExtractionOptions,
+) -> Result<(Timeline, Vec<AnalysisError>), AnalysisError> {
+    let mut diagnostics = Diagnostics::new(options.lenient);
+    let timeline =
+        extract_timeline_with_diagnostics(score, microtonal_alters, options, &mut diagnostics)?;
+    Ok((timeline, diagnostics.warnings))
+}
+
+struct Diagnostics {
+    lenient: bool,
+    warnings: Vec<AnalysisError>,
+}
+
+impl Diagnostics {
+    fn new(lenient: bool) -> Self {
+        Self {
+            lenient,
+            warnings: Vec::new(),
+        }
+    }
+
+    fn report(&mut self, error: AnalysisError) -> Result<(), AnalysisError> {
+        if !self.lenient {
+            return Err(error);
+        }
 
-pub fn extract_measure_data(score: &ScorePartwise) -> Vec<MeasureData> {
-    let mut measure_data = Vec::new();
-    let mut current_bpm = extract_bpm_from_score(score);
+        self.warnings.push(error);
+        Ok(())
+    }
+}
+
+fn extract_timeline_with_diagnostics(
+    score: &ScorePartwise,
+    microtonal_alters: &MicrotonalAlters,
+    options: &ExtractionOptions,
+    diagnostics: &mut Diagnostics,
+) -> Result<Timeline, AnalysisError> {
+    let aligned_measures = align_measures(score);
+    let mut timeline = Timeline::default();
     let mut current_time_sig = extract_time_signature_from_score(score);
 
-    for part in &score.content.part {
-        for part_element in &part.content {
-            if let PartElement::Measure(measure) = part_element {
-                if let Some(new_bpm) = extract_bpm_from_measure(measure) {
-                    current_bpm = new_bpm;
-                }
+    for aligned_measure in &aligned_measures {
+        if let Some(new_time_sig) = aligned_measure
+            .measures()
+            .find_map(|(_, measure)| extract_time_signature_from_measure(measure))
+        {
+            current_time_sig = new_time_sig;
+        }
 
-                if let Some(new_time_sig) = extract_time_signature_from_measure(measure) {
-                    current_time_sig = new_time_sig;
+        timeline.measures.push(TimelineMeasure {
+            number: aligned_measure.number.clone(),
+            tempo: Tempo::default(), // replaced once the tempo map is known
+            time_signature: current_time_sig.clone(),
+            start: RationalTime::ZERO, // known once the content of every part is
+            length: current_time_sig.measure_length(),
+            start_seconds: 0.0,
+            implicit: aligned_measure
+                .measures()
+                .any(|(_, measure)| measure.attributes.implicit == Some(YesNo::Yes)),
+            parts: Vec::new(),
+        });
+    }
+
+    let mut tempo_marks: Vec<Vec<TempoMark>> = vec![Vec::new(); aligned_measures.len()];
+    let mut navigation = vec![MeasureNavigation::default(); aligned_measures.len()];
+    let mut content_lengths = vec![RationalTime::ZERO; aligned_measures.len()];
+    for (part_index, part) in score.content.part.iter().enumerate() {
+        let mut part_state = PartState::default();
+        let mut open_ties: HashMap<TieKey, usize> = HashMap::new();
+
+        for (measure_index, aligned_measure) in aligned_measures.iter().enumerate() {
+            if let Some(measure) = aligned_measure.part_measures[part_index] {
+                let timeline_measure = &mut timeline.measures[measure_index];
+
+                part_state.cursor.start_measure();
+                let measure_alters = microtonal_alters.for_measure(
+                    &part.attributes.id,
+                    &aligned_measure.number,
+                    aligned_measure.occurrence,
+                );
+                let measure_events = extract_events_from_measure(
+                    measure,
+                    &part.attributes.id,
+                    measure_index,
+                    measure_alters,
+                    &mut part_state,
+                    &options.tempo_terms,
+                    diagnostics,
+                )?;
+
+                timeline_measure.parts.push(PartMeasure {
+                    part_id: part.attributes.id.to_string(),
+                    fill: part_state
+                        .cursor
+                        .measure_fill(&timeline_measure.time_signature),
+                    key_changes: measure_events.key_changes,
+                    clef_changes: measure_events.clef_changes,
+                });
+                add_notes_resolving_ties(
+                    &mut timeline.notes,
+                    measure_events.notes,
+                    &measure_events.note_ties,
+                    &mut open_ties,
+                );
+
+                // The first part with tempo marks in a measure decides its tempo
+                if tempo_marks[measure_index].is_empty() {
+                    tempo_marks[measure_index] = measure_events.tempo_marks;
                 }
+                navigation[measure_index].merge(&MeasureNavigation::from_measure(measure));
+                content_lengths[measure_index] =
+                    content_lengths[measure_index].max(part_state.cursor.content_length());
+            }
+        }
+    }
+
+    // Implicit measures and a short final measure last as long as their longest
+    // part. Any other measure keeps the length of its time signature, and the
+    // parts that do not fill it are marked incomplete.
+    let last_index = timeline.measures.len().saturating_sub(1);
+    let mut start = RationalTime::ZERO;
+    for (measure_index, (measure, &content)) in timeline
+        .measures
+        .iter_mut()
+        .zip(&content_lengths)
+        .enumerate()
+    {
+        // A piece of a single measure has no final measure to complete a pickup
+        let is_final = measure_index == last_index && measure_index > 0;
+        if (measure.implicit || is_final) && content > RationalTime::ZERO {
+            measure.length = content;
+        }
+        measure.start = start;
+        start += measure.length;
+    }
 
-                let note_count = get_nr_notes_in_measure(measure);
-                let pitches = extract_pitches_from_measure(measure);
+    if options.measure_order == MeasureOrder::Performance {
+        let order = performance_order(&navigation);
+        tempo_marks = order
+            .iter()
+            .map(|&index| tempo_marks[index].clone())
+            .collect();
+        unroll_measures(&mut timeline, &order);
+    }
 
-                measure_data.push(MeasureData {
-                    note_count,
-                    tempo_bpm: current_bpm,
-                    time_signature: current_time_sig,
-                    pitches,
-                });
+    timeline.tempo_map = build_tempo_map(&timeline, &tempo_marks, &options.tempo_ramp);
+    apply_tempo_map(&mut timeline);
+
+    timeline
+        .notes
+        .sort_by(|a, b| a.onset_seconds.total_cmp(&b.onset_seconds));
+    Ok(timeline)
+}
+
+// Tied continuations lengthen the note they continue instead of starting a new one
+fn add_notes_resolving_ties(
+    notes: &mut Vec<NoteEvent>,
+    measure_notes: Vec<NoteEvent>,
+    note_ties: &[NoteTies],
+    open_ties: &mut HashMap<TieKey, usize>,
+) {
+    for (note, ties) in measure_notes.into_iter().zip(note_ties) {
+        let key = (note.written_pitch, note.voice.clone(), note.staff);
+        let index = match open_ties.remove(&key) {
+            Some(index) if ties.stops => {
+                notes[index].duration += note.duration;
+                index
+            }
+            _ => {
+                notes.push(note);
+                notes.len() - 1
             }
+        };
+        if ties.starts {
+            open_ties.insert(key, index);
         }
     }
+}
+
+// Repeats every measure, and the notes in it, once for each time it is played
+fn unroll_measures(timeline: &mut Timeline, order: &[usize]) {
+    let mut notes_by_measure: Vec<Vec<&NoteEvent>> = vec![Vec::new(); timeline.measures.len()];
+    for note in &timeline.notes {
+        notes_by_measure[note.measure_index].push(note);
+    }
+
+    let mut measures = Vec::with_capacity(order.len());
+    let mut notes = Vec::new();
+    let mut start = RationalTime::ZERO;
+    for (measure_index, &written_index) in order.iter().enumerate() {
+        let mut measure = timeline.measures[written_index].clone();
+        measure.start = start;
+        start += measure.length;
+        measures.push(measure);
+
+        notes.extend(
+            notes_by_measure[written_index]
+                .iter()
+                .map(|&note| NoteEvent {
+                    measure_index,
+                    ..note.clone()
+                }),
+        );
+    }
 
-    measure_data
+    timeline.measures = measures;
+    timeline.notes = notes;
 }
 
-fn extract_bpm_from_score(score: &ScorePartwise) -> f64 {
-    const DEFAULT_BPM: f64 = 120.0;
+fn build_tempo_map(
+    timeline: &Timeline,
+    tempo_marks: &[Vec<TempoMark>],
+    ramp_options: &TempoRampOptions,
+) -> TempoMap {
+    let marks: Vec<(usize, f64, &TempoMark)> = timeline
+        .measures
+        .iter()
+        .zip(tempo_marks)
+        .enumerate()
+        .flat_map(|(measure_index, (measure, marks))| {
+            // A metronome or sound tempo is more precise than a tempo term
+            let has_explicit_tempo = marks.iter().any(|mark| mark.change.is_explicit());
+            marks
+                .iter()
+                .filter(move |mark| {
+                    !(has_explicit_tempo && matches!(mark.change, TempoChange::Term(_)))
+                })
+                .map(move |mark| {
+                    (
+                        measure_index,
+                        measure.start_quarters() + mark.position_quarters,
+                        mark,
+                    )
+                })
+        })
+        .collect();
+
+    // The first tempo of the piece applies from its start
+    let (initial_tempo, initial_source) = marks
+        .iter()
+        .take_while(|&&(measure_index, _, _)| measure_index == 0)
+        .find(|(_, _, mark)| mark.change.sets_tempo())
+        .map_or((Tempo::default(), TempoSource::Default), |(_, _, mark)| {
+            let meter = &timeline.measures[0].time_signature;
+            (
+                mark.change.resolve(&Tempo::default(), meter),
+                mark.source.clone(),
+            )
+        });
+
+    let mut tempo_map = TempoMap::new(initial_tempo, initial_source);
+    let mut current_tempo = initial_tempo;
+    let mut tempo_before_ramps: Option<Tempo> = None;
+
+    for (index, &(measure_index, position_quarters, mark)) in marks.iter().enumerate() {
+        let meter = &timeline.measures[measure_index].time_signature;
+        let new_tempo = match mark.change {
+            TempoChange::Absolute(_) | TempoChange::Modulation { .. } | TempoChange::Term(_) => {
+                mark.change.resolve(&current_tempo, meter)
+            }
+            TempoChange::ATempo => tempo_before_ramps.unwrap_or(current_tempo),
+            TempoChange::TempoPrimo => initial_tempo,
+            TempoChange::Ramp(direction) => {
+                let factor = match direction {
+                    RampDirection::Slower => ramp_options.slower_factor,
+                    RampDirection::Faster => ramp_options.faster_factor,
+                };
+                let end_quarters = find_ramp_end(&marks[index + 1..], measure_index, timeline);
+                let end_tempo = current_tempo.scaled(factor);
+
+                tempo_before_ramps.get_or_insert(current_tempo);
+                tempo_map.set_ramp(
+                    position_quarters,
+                    end_quarters,
+                    end_tempo,
+                    ramp_options.shape,
+                    mark.source.clone(),
+                );
+                current_tempo = end_tempo;
+                continue;
+            }
+            TempoChange::RampEnd => continue,
+        };
 
-    if let Some(first_part) = score.content.part.first()
-        && let Some(PartElement::Measure(first_measure)) = first_part.content.first()
-        && let Some(tempo) = extract_bpm_from_measure(first_measure)
-    {
-        return tempo;
+        tempo_before_ramps = None;
+        current_tempo = new_tempo;
+        tempo_map.set_tempo(position_quarters, current_tempo, mark.source.clone());
     }
 
-    DEFAULT_BPM
+    tempo_map
 }
 
-fn extract_bpm_from_measure(measure: &Measure) -> Option<f64> {
-    for measure_content in &measure.content {
-        if let MeasureElement::Direction(direction) = measure_content {
-            if let Some(sound) = &direction.content.sound
-                && let Some(tempo) = &sound.attributes.tempo
-            {
-                return Some(**tempo);
+// A ramp runs to the end of its dashes, or else through the next measure,
+// and never past the next tempo mark
+fn find_ramp_end(
+    following_marks: &[(usize, f64, &TempoMark)],
+    measure_index: usize,
+    timeline: &Timeline,
+) -> f64 {
+    let fallback_measure = timeline
+        .measures
+        .get(measure_index + 1)
+        .unwrap_or(&timeline.measures[measure_index]);
+    let fallback_quarters = fallback_measure.end().to_quarters();
+
+    match following_marks.first() {
+        Some(&(_, position_quarters, mark)) if mark.change == TempoChange::RampEnd => {
+            position_quarters
+        }
+        Some(&(_, position_quarters, _)) => position_quarters.min(fallback_quarters),
+        None => fallback_quarters,
+    }
+}
+
+fn apply_tempo_map(timeline: &mut Timeline) {
+    let tempo_map = &timeline.tempo_map;
+
+    for measure in &mut timeline.measures {
+        measure.start_seconds = measure.start.to_seconds(tempo_map);
+        measure.tempo =
+            tempo_map.average_tempo(measure.start_quarters(), measure.end().to_quarters());
+    }
+
+    for note in &mut timeline.notes {
+        let onset = timeline.measures[note.measure_index].start + note.onset;
+        note.onset_seconds = onset.to_seconds(tempo_map);
+        note.duration_seconds = (onset + note.duration).to_seconds(tempo_map) - note.onset_seconds;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RampDirection {
+    Slower,
+    Faster,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TempoChange {
+    Absolute(Tempo),
+    Modulation { old_unit: f64, new_unit: f64 }, // relative to the tempo in effect
+    Ramp(RampDirection),
+    RampEnd,    // end of the dashes following a ramp
+    ATempo,     // back to the tempo before the last ramps
+    TempoPrimo, // back to the tempo the piece started with
+    Term(f64),  // beats per minute for a term like "Allegro", in beats of the meter
+}
+
+// The beats of an additive meter differ in length, so its terms count quarters
+fn tempo_term_unit_in_quarters(meter: &TimeSignature) -> f64 {
+    match meter.grouping {
+        BeatGrouping::Additive(_) => 1.0,
+        _ => meter.beat_unit_in_quarters(),
+    }
+}
+
+impl TempoChange {
+    fn is_explicit(&self) -> bool {
+        matches!(
+            self,
+            TempoChange::Absolute(_) | TempoChange::Modulation { .. }
+        )
+    }
+
+    fn sets_tempo(&self) -> bool {
+        self.is_explicit() || matches!(self, TempoChange::Term(_))
+    }
+
+    fn resolve(&self, current_tempo: &Tempo, meter: &TimeSignature) -> Tempo {
+        match self {
+            TempoChange::Absolute(tempo) => *tempo,
+            TempoChange::Modulation { old_unit, new_unit } => {
+                current_tempo.metric_modulation(*old_unit, *new_unit)
+            }
+            TempoChange::Term(beats_per_minute) => {
+                Tempo::new(*beats_per_minute, tempo_term_unit_in_quarters(meter))
             }
+            _ => *current_tempo,
+        }
+    }
+}
 
-            for direction_type in &direction.content.direction_type {
-                if let musicxml::elements::DirectionTypeContents::Metronome(metronome) =
-                    &direction_type.content
-                {
-                    return extract_bpm_from_metronome(metronome);
+#[derive(Debug, Clone, PartialEq)]
+struct TempoMark {
+    position_quarters: f64, // relative to the start of the measure
+    change: TempoChange,
+    source: TempoSource,
+}
+
+struct MeasureEvents {
+    notes: Vec<NoteEvent>,
+    note_ties: Vec<NoteTies>,    // one for every note
+    tempo_marks: Vec<TempoMark>, // sorted by position
+    key_changes: Vec<KeyChange>,
+    clef_changes: Vec<ClefChange>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct NoteTies {
+    starts: bool, // tied to the next note of the same pitch
+    stops: bool,  // continues the previous note of the same pitch
+}
+
+impl NoteTies {
+    fn from_ties(ties: &[Tie]) -> Self {
+        Self {
+            starts: ties
+                .iter()
+                .any(|tie| tie.attributes.r#type == StartStop::Start),
+            stops: ties
+                .iter()
+                .any(|tie| tie.attributes.r#type == StartStop::Stop),
+        }
+    }
+}
+
+// What reading a part carries over from one measure to the next
+struct PartState {
+    cursor: TimeCursor,
+    open_spanners: OpenSpanners,
+    transpositions: StaffTranspositions,
+}
+
+impl Default for PartState {
+    fn default() -> Self {
+        Self {
+            cursor: TimeCursor::new(DEFAULT_DIVISIONS),
+            open_spanners: OpenSpanners::default(),
+            transpositions: StaffTranspositions::default(),
+        }
+    }
+}
+
+// The <transpose> in effect, by staff number; None covers every other staff
+#[derive(Debug, Default)]
+struct StaffTranspositions(HashMap<Option<u32>, Transposition>);
+
+impl StaffTranspositions {
+    // A new set of <transpose> elements replaces the previous one
+    fn update(&mut self, transposes: &[Transpose]) {
+        if transposes.is_empty() {
+            return;
+        }
+        self.0 = transposes
+            .iter()
+            .map(|transpose| {
+                let staff = transpose.attributes.number.as_ref().map(|n| **n as u32);
+                (staff, extract_transposition(transpose))
+            })
+            .collect();
+    }
+
+    fn for_staff(&self, staff: u32) -> Transposition {
+        self.0
+            .get(&Some(staff))
+            .or_else(|| self.0.get(&None))
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+fn extract_transposition(transpose: &Transpose) -> Transposition {
+    let content = &transpose.content;
+    Transposition {
+        double: content
+            .double
+            .as_ref()
+            .map(|double| match double.attributes.above {
+                Some(YesNo::Yes) => 1,
+                _ => -1,
+            }),
+        ..Transposition::new(
+            content
+                .diatonic
+                .as_ref()
+                .map(|diatonic| diatonic.content as i32),
+            *content.chromatic.content as i32,
+            content
+                .octave_change
+                .as_ref()
+                .map_or(0, |octave_change| octave_change.content as i32),
+        )
+    }
+}
+
+// Spanners that can continue into the next measure of a part
+#[derive(Debug, Default)]
+struct OpenSpanners {
+    ramp_dashes: Option<u8>, // number of the dashes after a rit. or accel.
+    tuplets: HashMap<Option<String>, Vec<OpenTuplet>>, // by voice, outermost first
+}
+
+#[derive(Debug, Clone, Copy)]
+struct OpenTuplet {
+    number: u8,
+    ratio: TupletRatio,
+}
+
+impl OpenSpanners {
+    // The tuplets a note is in, outermost first. Tuplet brackets give the
+    // nesting; without them the time modification counts as a single tuplet.
+    fn enter_tuplets(&mut self, note: &Note) -> Vec<TupletRatio> {
+        let voice = note
+            .content
+            .voice
+            .as_ref()
+            .map(|voice| voice.content.clone());
+        let open = self.tuplets.entry(voice).or_default();
+        let Some(time_modification) = &note.content.time_modification else {
+            open.clear(); // a note outside any tuplet ends tuplets left open by mistake
+            return Vec::new();
+        };
+        let combined = TupletRatio::new(
+            *time_modification.content.actual_notes.content,
+            *time_modification.content.normal_notes.content,
+        );
+
+        let tuplets = note
+            .content
+            .notations
+            .iter()
+            .flat_map(|notations| &notations.content.notations)
+            .filter_map(|notation| match notation {
+                NotationContentTypes::Tuplet(tuplet) => Some(tuplet),
+                _ => None,
+            });
+        let mut stopped = Vec::new();
+        for tuplet in tuplets {
+            let number = tuplet
+                .attributes
+                .number
+                .as_ref()
+                .map_or(1, |number| **number);
+            match tuplet.attributes.r#type {
+                StartStop::Start => {
+                    let outer = open.iter().fold(TupletRatio::new(1, 1), |outer, tuplet| {
+                        TupletRatio::new(
+                            outer.actual_notes * tuplet.ratio.actual_notes,
+                            outer.normal_notes * tuplet.ratio.normal_notes,
+                        )
+                    });
+                    if let Some(ratio) =
+                        extract_tuplet_ratio(tuplet).or_else(|| combined.within(outer))
+                        && ratio.actual_notes != ratio.normal_notes
+                    {
+                        open.push(OpenTuplet { number, ratio });
+                    }
                 }
+                StartStop::Stop => stopped.push(number),
             }
         }
+
+        let ratios = if open.is_empty() {
+            vec![combined]
+        } else {
+            open.iter().map(|tuplet| tuplet.ratio).collect()
+        };
+        open.retain(|tuplet| !stopped.contains(&tuplet.number));
+        ratios
     }
-    None
 }
 
-fn extract_bpm_from_metronome(metronome: &musicxml::elements::Metronome) -> Option<f64> {
-    match &metronome.content {
-        MetronomeContents::BeatBased(beat_based) => extract_bpm_from_beat_based(beat_based),
-        _ => {
-            panic!("Unsupported metronome content type for BPM extraction");
+fn extract_tuplet_ratio(tuplet: &Tuplet) -> Option<TupletRatio> {
+    let actual = tuplet
+        .content
+        .tuplet_actual
+        .as_ref()?
+        .content
+        .tuplet_number
+        .as_ref()?;
+    let normal = tuplet
+        .content
+        .tuplet_normal
+        .as_ref()?
+        .content
+        .tuplet_number
+        .as_ref()?;
+    Some(TupletRatio::new(*actual.content, *normal.content))
+}
+
+type TieKey = (Pitch, Option<String>, u32); // pitch, voice and staff
+
+struct AlignedMeasure<'a> {
+    number: String,
+    occurrence: usize, // counts from 1 over the measures of a part with this number
+    part_measures: Vec<Option<&'a Measure>>,
+}
+
+impl<'a> AlignedMeasure<'a> {
+    fn measures(&self) -> impl Iterator<Item = (usize, &'a Measure)> {
+        self.part_measures
+            .iter()
+            .enumerate()
+            .filter_map(|(part_index, measure)| Some((part_index, (*measure)?)))
+    }
+}
+
+fn align_measures(score: &ScorePartwise) -> Vec<AlignedMeasure<'_>> {
+    let part_count = score.content.part.len();
+    let mut aligned_measures: Vec<AlignedMeasure> = Vec::new();
+    let mut index_by_number: HashMap<(String, usize), usize> = HashMap::new();
+
+    for (part_index, part) in score.content.part.iter().enumerate() {
+        let mut occurrences: HashMap<String, usize> = HashMap::new();
+
+        for part_element in &part.content {
+            if let PartElement::Measure(measure) = part_element {
+                let number = measure.attributes.number.to_string();
+                let occurrence = occurrences.entry(number.clone()).or_default();
+                *occurrence += 1;
+
+                let index = *index_by_number
+                    .entry((number.clone(), *occurrence))
+                    .or_insert_with(|| {
+                        aligned_measures.push(AlignedMeasure {
+                            number,
+                            occurrence: *occurrence,
+                            part_measures: vec![None; part_count],
+                        });
+                        aligned_measures.len() - 1
+                    });
+                aligned_measures[index].part_measures[part_index] = Some(measure);
+            }
         }
     }
+
+    aligned_measures
 }
 
-fn extract_bpm_from_beat_based(beat_based: &musicxml::elements::BeatBased) -> Option<f64> {
-    let per_minute = match &beat_based.equals {
-        musicxml::elements::BeatEquation::BPM(per_minute) => per_minute.content.parse().ok()?,
-        _ => {
-            panic!(
-                "Unsupported beat equation for BPM extraction: {:?}",
-                beat_based.equals
-            );
+fn extract_tempo_change_from_direction(
+    direction: &Direction,
+    location: &MeasureLocation,
+    tempo_terms: &TempoTermTable,
+) -> Result<Option<(TempoChange, TempoSource)>, AnalysisError> {
+    if let Some(sound) = &direction.content.sound
+        && let Some(change) = extract_tempo_change_from_sound(sound)
+    {
+        return Ok(Some((change, TempoSource::Sound)));
+    }
+
+    for direction_type in &direction.content.direction_type {
+        if let DirectionTypeContents::Metronome(metronome) = &direction_type.content {
+            let change = extract_tempo_change_from_metronome(metronome, location)?;
+            return Ok(change.map(|change| (change, TempoSource::Metronome)));
         }
-    };
+    }
+
+    let words: Vec<&str> = direction
+        .content
+        .direction_type
+        .iter()
+        .filter_map(|direction_type| match &direction_type.content {
+            DirectionTypeContents::Words(words) => Some(words),
+            _ => None,
+        })
+        .flatten()
+        .map(|words| words.content.as_str())
+        .collect();
+    let text = words.join(" ");
+    // Dashes typed into the text, as in "rit. - - -", are not part of the term
+    let text = text.trim_end_matches(|c: char| c == '-' || c.is_whitespace());
+    let source = TempoSource::Text(text.split_whitespace().collect::<Vec<_>>().join(" "));
+    Ok(extract_tempo_change_from_words(text, tempo_terms).map(|change| (change, source)))
+}
+
+fn extract_tempo_change_from_words(
+    text: &str,
+    tempo_terms: &TempoTermTable,
+) -> Option<TempoChange> {
+    let lowercase_text = text.to_lowercase();
+    let text = lowercase_text.as_str();
+    let terms: Vec<&str> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .collect();
+
+    if terms.windows(2).any(|pair| pair == ["a", "tempo"]) {
+        return Some(TempoChange::ATempo);
+    }
+    if terms
+        .windows(2)
+        .any(|pair| pair[0] == "tempo" && matches!(pair[1], "primo" | "i" | "1"))
+    {
+        return Some(TempoChange::TempoPrimo);
+    }
+    if terms.iter().any(|term| is_slowing_term(term)) {
+        return Some(TempoChange::Ramp(RampDirection::Slower));
+    }
+    if terms.iter().any(|term| is_quickening_term(term)) {
+        return Some(TempoChange::Ramp(RampDirection::Faster));
+    }
+    tempo_terms.beats_per_minute(text).map(TempoChange::Term)
+}
+
+// Terms are split at punctuation, so "rit." and "ritard." arrive as "rit" and
+// "ritard"; a bare "rit" prefix would take in "ritornello" and "ritmico" too
+const RITARDANDO_FORMS: [&str; 5] = ["rit", "ritard", "ritardando", "riten", "ritenuto"];
+
+fn is_slowing_term(term: &str) -> bool {
+    RITARDANDO_FORMS.contains(&term)
+        || term.starts_with("rall")
+        || term.starts_with("allarg")
+        || term.starts_with("slent")
+}
+
+fn is_quickening_term(term: &str) -> bool {
+    term.starts_with("accel") || term == "string" || term.starts_with("stringend")
+}
 
-    let quarter_note_bpm = match &beat_based.beat_unit.content {
-        NoteTypeValue::Quarter => per_minute,
-        NoteTypeValue::Half => per_minute * 2.0,
-        NoteTypeValue::Eighth => per_minute / 2.0,
-        _ => {
-            panic!(
-                "Unsupported beat unit for BPM extraction: {:?}",
-                beat_based.beat_unit.content
-            );
+fn find_dashes(direction: &Direction, dashes_type: StartStopContinue) -> Option<u8> {
+    direction
+        .content
+        .direction_type
+        .iter()
+        .find_map(|direction_type| match &direction_type.content {
+            DirectionTypeContents::Dashes(dashes) if dashes.attributes.r#type == dashes_type => {
+                Some(
+                    dashes
+                        .attributes
+                        .number
+                        .as_ref()
+                        .map_or(1, |number| **number),
+                )
+            }
+            _ => None,
+        })
+}
+
+fn extract_tempo_change_from_sound(sound: &Sound) -> Option<TempoChange> {
+    let tempo = sound.attributes.tempo.as_ref()?;
+    Some(TempoChange::Absolute(Tempo::quarter_notes(**tempo)))
+}
+
+fn extract_tempo_change_from_metronome(
+    metronome: &musicxml::elements::Metronome,
+    location: &MeasureLocation,
+) -> Result<Option<TempoChange>, AnalysisError> {
+    match &metronome.content {
+        MetronomeContents::BeatBased(beat_based) => {
+            Ok(extract_tempo_change_from_beat_based(beat_based))
         }
-    };
+        MetronomeContents::MetronomeBased(_) => {
+            Err(AnalysisError::UnsupportedMetronome(location.clone()))
+        }
+    }
+}
+
+fn extract_tempo_change_from_beat_based(
+    beat_based: &musicxml::elements::BeatBased,
+) -> Option<TempoChange> {
+    let beat_unit = beat_unit_in_quarters(
+        &beat_based.beat_unit,
+        &beat_based.beat_unit_dot,
+        &beat_based.beat_unit_tied,
+    );
+
+    match &beat_based.equals {
+        BeatEquation::BPM(per_minute) => Some(TempoChange::Absolute(Tempo::new(
+            per_minute.content.trim().parse().ok()?,
+            beat_unit,
+        ))),
+        BeatEquation::Beats(equation) => Some(TempoChange::Modulation {
+            old_unit: beat_unit,
+            new_unit: beat_unit_in_quarters(
+                &equation.beat_unit,
+                &equation.beat_unit_dot,
+                &equation.beat_unit_tied,
+            ),
+        }),
+    }
+}
+
+fn beat_unit_in_quarters(
+    beat_unit: &BeatUnit,
+    beat_unit_dots: &[BeatUnitDot],
+    beat_unit_tied: &[BeatUnitTied],
+) -> f64 {
+    let tied_length: f64 = beat_unit_tied
+        .iter()
+        .map(|tied| {
+            beat_unit_in_quarters(&tied.content.beat_unit, &tied.content.beat_unit_dot, &[])
+        })
+        .sum();
+    dotted_length(
+        note_type_value_in_quarters(&beat_unit.content),
+        beat_unit_dots.len(),
+    ) + tied_length
+}
 
-    if beat_based.beat_unit_dot.is_empty() {
-        Some(quarter_note_bpm)
-    } else {
-        Some(quarter_note_bpm * 1.5)
+fn note_type_value_in_quarters(note_type_value: &NoteTypeValue) -> f64 {
+    match note_type_value {
+        NoteTypeValue::Maxima => 32.0,
+        NoteTypeValue::Long => 16.0,
+        NoteTypeValue::Breve => 8.0,
+        NoteTypeValue::Whole => 4.0,
+        NoteTypeValue::Half => 2.0,
+        NoteTypeValue::Quarter => 1.0,
+        NoteTypeValue::Eighth => 1.0 / 2.0,
+        NoteTypeValue::Sixteenth => 1.0 / 4.0,
+        NoteTypeValue::ThirtySecond => 1.0 / 8.0,
+        NoteTypeValue::SixtyFourth => 1.0 / 16.0,
+        NoteTypeValue::OneHundredTwentyEighth => 1.0 / 32.0,
+        NoteTypeValue::TwoHundredFiftySixth => 1.0 / 64.0,
+        NoteTypeValue::FiveHundredTwelfth => 1.0 / 128.0,
+        NoteTypeValue::OneThousandTwentyFourth => 1.0 / 256.0,
     }
 }
 
+// Each dot adds half of the previous addition: 1.5x, 1.75x, 1.875x, ...
+fn dotted_length(length: f64, dots: usize) -> f64 {
+    length * (2.0 - 0.5_f64.powi(dots as i32))
+}
+
 fn extract_time_signature_from_score(score: &ScorePartwise) -> TimeSignature {
     if let Some(first_part) = score.content.part.first() {
         for part_element in &first_part.content {
@@ -126,10 +878,7 @@ fn extract_time_signature_from_score(score: &ScorePartwise) -> TimeSignature {
         }
     }
 
-    TimeSignature {
-        numerator: 4,
-        denominator: 4,
-    }
+    TimeSignature::new(4, 4)
 }
 
 fn extract_time_signature_from_measure(measure: &Measure) -> Option<TimeSignature> {
@@ -137,95 +886,460 @@ fn extract_time_signature_from_measure(measure: &Measure) -> Option<TimeSignatur
         if let MeasureElement::Attributes(attributes) = measure_content
             && let Some(first_time) = attributes.content.time.first()
         {
-            let numerator = first_time
-                .content
-                .beats
-                .first()
-                .unwrap()
-                .beats
-                .content
-                .parse()
-                .ok()?;
-            let denominator = first_time
-                .content
-                .beats
-                .first()
-                .unwrap()
-                .beat_type
-                .content
-                .parse()
-                .ok()?;
-            return Some(TimeSignature {
-                numerator,
-                denominator,
-            });
+            let time_beats = first_time.content.beats.first()?;
+            return parse_time_signature(&time_beats.beats.content, &time_beats.beat_type.content);
         }
     }
 
     None
 }
 
-fn get_nr_notes_in_measure(measure: &musicxml::elements::Measure) -> u32 {
-    let mut nr_notes = 0;
-    for measure_content in &measure.content {
-        if let MeasureElement::Note(note) = measure_content
-            && let NoteType::Normal(normal_info) = &note.content.info
-            && let AudibleType::Pitch(_) = &normal_info.audible
-        {
-            nr_notes += 1;
-        }
+// None for beat types that are no note value, like 0 or 3, as well as for
+// anything that is not a number
+fn parse_time_signature(beats: &str, beat_type: &str) -> Option<TimeSignature> {
+    let denominator: u32 = beat_type.trim().parse().ok()?;
+    if !denominator.is_power_of_two() {
+        return None;
+    }
+
+    if beats.contains('+') {
+        let groups = beats
+            .split('+')
+            .map(|group| group.trim().parse().ok())
+            .collect::<Option<Vec<u32>>>()?;
+        return Some(TimeSignature::additive(groups, denominator));
     }
-    nr_notes
+
+    Some(TimeSignature::new(beats.trim().parse().ok()?, denominator))
 }
 
-fn extract_pitches_from_measure(measure: &Measure) -> HashSet<Pitch> {
-    let mut pitches = HashSet::new();
+fn extract_events_from_measure(
+    measure: &Measure,
+    part_id: &str,
+    measure_index: usize,
+    measure_alters: Option<&MeasureAlters>,
+    part_state: &mut PartState,
+    tempo_terms: &TempoTermTable,
+    diagnostics: &mut Diagnostics,
+) -> Result<MeasureEvents, AnalysisError> {
+    let PartState {
+        cursor,
+        open_spanners,
+        transpositions,
+    } = part_state;
+    let location = MeasureLocation::new(part_id, &measure.attributes.number);
+    let mut note_events: Vec<NoteEvent> = Vec::new();
+    let mut note_ties: Vec<NoteTies> = Vec::new();
+    let mut tempo_marks: Vec<TempoMark> = Vec::new();
+    let mut key_changes: Vec<KeyChange> = Vec::new();
+    let mut clef_changes: Vec<ClefChange> = Vec::new();
+    let mut note_index = 0;
+    let mut key_index = 0;
 
     for measure_content in &measure.content {
-        if let MeasureElement::Note(note) = measure_content
-            && let NoteType::Normal(normal_info) = &note.content.info
-            && let AudibleType::Pitch(pitch_info) = &normal_info.audible
-        {
-            let note_name = extract_note_name_from_pitch(pitch_info);
-            let octave = *pitch_info.content.octave.content;
-            let accidental = get_accidental_from_pitch(pitch_info);
-            pitches.insert(Pitch::new(note_name, octave, accidental));
+        match measure_content {
+            MeasureElement::Attributes(attributes) => {
+                if let Some(divisions) = &attributes.content.divisions
+                    && *divisions.content > 0
+                {
+                    cursor.set_divisions(*divisions.content);
+                }
+                transpositions.update(&attributes.content.transpose);
+
+                let position_quarters = cursor.quarters_at_offset(0);
+                for key in &attributes.content.key {
+                    let key_alter_cents = measure_alters
+                        .and_then(|measure_alters| measure_alters.key_cents(key_index));
+                    key_index += 1;
+                    key_changes.push(KeyChange {
+                        staff: key.attributes.number.as_ref().map(|number| **number as u32),
+                        position_quarters,
+                        key: extract_key_signature(key, key_alter_cents),
+                    });
+                }
+                clef_changes.extend(attributes.content.clef.iter().map(|clef| {
+                    ClefChange {
+                        staff: clef
+                            .attributes
+                            .number
+                            .as_ref()
+                            .map_or(1, |number| **number as u32),
+                        position_quarters,
+                        clef: extract_clef(clef),
+                    }
+                }));
+            }
+            MeasureElement::Backup(backup) => cursor.backup(*backup.content.duration.content),
+            MeasureElement::Forward(forward) => cursor.advance(*forward.content.duration.content),
+            MeasureElement::Direction(direction) => {
+                let offset = direction
+                    .content
+                    .offset
+                    .as_ref()
+                    .map_or(0, |offset| *offset.content);
+                let position_quarters = cursor.quarters_at_offset(offset);
+
+                if open_spanners.ramp_dashes.is_some()
+                    && find_dashes(direction, StartStopContinue::Stop) == open_spanners.ramp_dashes
+                {
+                    open_spanners.ramp_dashes = None;
+                    tempo_marks.push(TempoMark {
+                        position_quarters,
+                        change: TempoChange::RampEnd,
+                        source: TempoSource::Default,
+                    });
+                }
+
+                match extract_tempo_change_from_direction(direction, &location, tempo_terms) {
+                    Ok(Some((change, source))) => {
+                        if let TempoChange::Ramp(_) = change {
+                            open_spanners.ramp_dashes =
+                                find_dashes(direction, StartStopContinue::Start);
+                        }
+                        tempo_marks.push(TempoMark {
+                            position_quarters,
+                            change,
+                            source,
+                        });
+                    }
+                    Ok(None) => {}
+                    Err(error) => diagnostics.report(error)?,
+                }
+            }
+            MeasureElement::Sound(sound) => {
+                if let Some(change) = extract_tempo_change_from_sound(sound) {
+                    tempo_marks.push(TempoMark {
+                        position_quarters: cursor.quarters_at_offset(0),
+                        change,
+                        source: TempoSource::Sound,
+                    });
+                }
+            }
+            MeasureElement::Note(note) => {
+                let alter_cents =
+                    measure_alters.and_then(|measure_alters| measure_alters.note_cents(note_index));
+                note_index += 1;
+                let (audible, duration, is_chord, ties, kind) = match &note.content.info {
+                    NoteType::Normal(info) => (
+                        &info.audible,
+                        *info.duration.content,
+                        info.chord.is_some(),
+                        NoteTies::from_ties(&info.tie),
+                        NoteKind::Regular,
+                    ),
+                    // Grace notes take no time of their own in the measure
+                    NoteType::Grace(info) => match &info.info {
+                        GraceType::Normal(grace_info) => (
+                            &grace_info.audible,
+                            0,
+                            grace_info.chord.is_some(),
+                            NoteTies::default(),
+                            NoteKind::Grace(extract_grace_note(&info.grace, cursor.divisions)),
+                        ),
+                        GraceType::Cue(grace_info) => (
+                            &grace_info.audible,
+                            0,
+                            grace_info.chord.is_some(),
+                            NoteTies::default(),
+                            NoteKind::Cue,
+                        ),
+                    },
+                    NoteType::Cue(info) => (
+                        &info.audible,
+                        *info.duration.content,
+                        info.chord.is_some(),
+                        NoteTies::default(),
+                        NoteKind::Cue,
+                    ),
+                };
+                let onset = cursor.place_note(duration, is_chord);
+                let tuplets = open_spanners.enter_tuplets(note);
+
+                let (pitch, is_unpitched) = match audible {
+                    AudibleType::Pitch(pitch_info) => {
+                        let pitch = extract_pitch(pitch_info, alter_cents, &location, diagnostics)?;
+                        (pitch, false)
+                    }
+                    AudibleType::Unpitched(unpitched) => (extract_display_pitch(unpitched), true),
+                    AudibleType::Rest(_) => continue,
+                };
+                let staff = note
+                    .content
+                    .staff
+                    .as_ref()
+                    .map_or(1, |staff| *staff.content);
+                let transposition = if is_unpitched {
+                    Transposition::default()
+                } else {
+                    transpositions.for_staff(staff)
+                };
+
+                if is_chord
+                    && let Some(previous) = note_events.last_mut()
+                    && previous.onset == onset
+                {
+                    previous.is_chord_member = true;
+                }
+
+                note_events.push(NoteEvent {
+                    part_id: part_id.to_string(),
+                    measure_index,
+                    voice: note
+                        .content
+                        .voice
+                        .as_ref()
+                        .map(|voice| voice.content.clone()),
+                    staff,
+                    written_pitch: pitch,
+                    sounding_pitch: transposition.sounding_pitch(&pitch),
+                    doubled_pitch: transposition.doubled_pitch(&pitch),
+                    is_unpitched,
+                    instrument_id: note
+                        .content
+                        .instrument
+                        .first()
+                        .map(|instrument| instrument.attributes.id.to_string()),
+                    onset,
+                    duration: cursor.duration(duration),
+                    onset_seconds: 0.0, // filled in from the tempo map
+                    duration_seconds: 0.0,
+                    is_chord_member: is_chord,
+                    kind,
+                    tuplets,
+                });
+                note_ties.push(ties);
+            }
+            _ => {}
         }
     }
 
-    pitches
+    tempo_marks.sort_by(|a, b| a.position_quarters.total_cmp(&b.position_quarters));
+    Ok(MeasureEvents {
+        notes: note_events,
+        note_ties,
+        tempo_marks,
+        key_changes,
+        clef_changes,
+    })
+}
+
+// key_alter_cents replaces the rounded <key-alter> of a microtonal key
+fn extract_key_signature(key: &Key, key_alter_cents: Option<i32>) -> KeySignature {
+    match &key.content {
+        KeyContents::Explicit(explicit) => KeySignature::traditional(
+            *explicit.fifths.content,
+            explicit
+                .mode
+                .as_ref()
+                .map(|mode| extract_key_mode(&mode.content)),
+        ),
+        // The musicxml crate reads a single altered step per non-traditional key
+        KeyContents::Relative(relative) => KeySignature::NonTraditional(vec![(
+            extract_note_name_from_step(&relative.key_step.content),
+            Accidental::from_cents(
+                key_alter_cents.unwrap_or(*relative.key_alter.content as i32 * 100),
+            ),
+        )]),
+    }
+}
+
+fn extract_key_mode(mode: &Mode) -> KeyMode {
+    match mode {
+        Mode::Major => KeyMode::Major,
+        Mode::Minor => KeyMode::Minor,
+        Mode::Ionian => KeyMode::Ionian,
+        Mode::Dorian => KeyMode::Dorian,
+        Mode::Phrygian => KeyMode::Phrygian,
+        Mode::Lydian => KeyMode::Lydian,
+        Mode::Mixolydian => KeyMode::Mixolydian,
+        Mode::Aeolian => KeyMode::Aeolian,
+        Mode::Locrian => KeyMode::Locrian,
+        Mode::None => KeyMode::None,
+    }
+}
+
+fn extract_clef(clef: &musicxml::elements::Clef) -> Clef {
+    let content = &clef.content;
+    let sign = match content.sign.content {
+        musicxml::datatypes::ClefSign::G => ClefSign::G,
+        musicxml::datatypes::ClefSign::F => ClefSign::F,
+        musicxml::datatypes::ClefSign::C => ClefSign::C,
+        musicxml::datatypes::ClefSign::Percussion => ClefSign::Percussion,
+        musicxml::datatypes::ClefSign::TAB => ClefSign::Tab,
+        musicxml::datatypes::ClefSign::Jianpu => ClefSign::Jianpu,
+        musicxml::datatypes::ClefSign::None => ClefSign::None,
+    };
+    Clef::new(
+        sign,
+        content.line.as_ref().map(|line| *line.content as i32),
+        content
+            .clef_octave_change
+            .as_ref()
+            .map_or(0, |octave_change| octave_change.content as i32),
+    )
+}
+
+fn extract_grace_note(grace: &Grace, divisions: u32) -> GraceNote {
+    let attributes = &grace.attributes;
+    GraceNote {
+        slash: attributes.slash == Some(YesNo::Yes),
+        steal_time_previous: attributes
+            .steal_time_previous
+            .as_ref()
+            .map(|percent| **percent),
+        steal_time_following: attributes
+            .steal_time_following
+            .as_ref()
+            .map(|percent| **percent),
+        make_time: attributes
+            .make_time
+            .as_ref()
+            .map(|make_time| **make_time as f64 / divisions as f64),
+    }
+}
+
+fn extract_pitch(
+    musicxml_pitch: &musicxml::elements::Pitch,
+    alter_cents: Option<i32>,
+    location: &MeasureLocation,
+    diagnostics: &mut Diagnostics,
+) -> Result<Pitch, AnalysisError> {
+    let note_name = extract_note_name_from_step(&musicxml_pitch.content.step.content);
+    let octave = *musicxml_pitch.content.octave.content;
+    let accidental = match get_accidental_from_pitch(musicxml_pitch, alter_cents, location) {
+        Ok(accidental) => accidental,
+        Err(error) => {
+            diagnostics.report(error)?;
+            Accidental::Natural
+        }
+    };
+    Ok(Pitch::new(note_name, octave, accidental))
+}
+
+// Where an unpitched note is written on the staff
+fn extract_display_pitch(unpitched: &Unpitched) -> Pitch {
+    let content = &unpitched.content;
+    Pitch::new(
+        extract_note_name_from_step(&content.display_step.content),
+        *content.display_octave.content,
+        Accidental::Natural,
+    )
 }
 
-fn extract_note_name_from_pitch(musicxml_pitch: &musicxml::elements::Pitch) -> NoteName {
-    match musicxml_pitch.content.step.content {
-        musicxml::datatypes::Step::A => NoteName::A,
-        musicxml::datatypes::Step::B => NoteName::B,
-        musicxml::datatypes::Step::C => NoteName::C,
-        musicxml::datatypes::Step::D => NoteName::D,
-        musicxml::datatypes::Step::E => NoteName::E,
-        musicxml::datatypes::Step::F => NoteName::F,
-        musicxml::datatypes::Step::G => NoteName::G,
+fn extract_note_name_from_step(step: &Step) -> NoteName {
+    match step {
+        Step::A => NoteName::A,
+        Step::B => NoteName::B,
+        Step::C => NoteName::C,
+        Step::D => NoteName::D,
+        Step::E => NoteName::E,
+        Step::F => NoteName::F,
+        Step::G => NoteName::G,
+    }
+}
+
+// Alterations beyond a triple sharp or flat are treated as notation errors
+const MAX_ALTER_CENTS: i32 = 300;
+
+// alter_cents replaces the rounded <alter> of a microtonal note
+fn get_accidental_from_pitch(
+    musicxml_pitch: &musicxml::elements::Pitch,
+    alter_cents: Option<i32>,
+    location: &MeasureLocation,
+) -> Result<Accidental, AnalysisError> {
+    let Some(alter) = &musicxml_pitch.content.alter else {
+        return Ok(Accidental::Natural);
+    };
+    let cents = alter_cents.unwrap_or(*alter.content as i32 * 100);
+    if cents.abs() > MAX_ALTER_CENTS {
+        return Err(AnalysisError::UnsupportedAlter(location.clone(), cents));
     }
+    Ok(Accidental::from_cents(cents))
 }
 
-fn get_accidental_from_pitch(musicxml_pitch: &musicxml::elements::Pitch) -> Accidental {
-    if let Some(alter) = &musicxml_pitch.content.alter {
-        match *alter.content {
-            -2 => Accidental::DoubleFlat,
-            -1 => Accidental::Flat,
-            0 => Accidental::Natural,
-            1 => Accidental::Sharp,
-            2 => Accidental::DoubleSharp,
-            _ => panic!("Unsupported alter {}", *alter.content),
+struct TimeCursor {
+    divisions: u32, // per quarter note, the unit of the durations read
+    position: RationalTime,
+    furthest_position: RationalTime,
+    last_onset: RationalTime,
+}
+
+impl TimeCursor {
+    fn new(divisions: u32) -> Self {
+        Self {
+            divisions,
+            position: RationalTime::ZERO,
+            furthest_position: RationalTime::ZERO,
+            last_onset: RationalTime::ZERO,
+        }
+    }
+
+    fn start_measure(&mut self) {
+        self.position = RationalTime::ZERO;
+        self.furthest_position = RationalTime::ZERO;
+        self.last_onset = RationalTime::ZERO;
+    }
+
+    // Positions are exact, so nothing read so far needs rescaling
+    fn set_divisions(&mut self, divisions: u32) {
+        self.divisions = divisions;
+    }
+
+    fn duration(&self, duration: u32) -> RationalTime {
+        RationalTime::from_divisions(duration as i64, self.divisions)
+    }
+
+    fn advance(&mut self, duration: u32) {
+        self.position += self.duration(duration);
+        self.furthest_position = self.furthest_position.max(self.position);
+    }
+
+    fn content_length(&self) -> RationalTime {
+        self.furthest_position
+    }
+
+    fn quarters_at_offset(&self, offset: i32) -> f64 {
+        let offset = RationalTime::from_divisions(offset as i64, self.divisions);
+        (self.position + offset)
+            .max(RationalTime::ZERO)
+            .to_quarters()
+    }
+
+    fn backup(&mut self, duration: u32) {
+        self.position = (self.position - self.duration(duration)).max(RationalTime::ZERO);
+    }
+
+    fn place_note(&mut self, duration: u32, is_chord: bool) -> RationalTime {
+        if is_chord {
+            return self.last_onset;
+        }
+
+        self.last_onset = self.position;
+        self.advance(duration);
+        self.last_onset
+    }
+
+    fn measure_fill(&self, time_signature: &TimeSignature) -> MeasureFill {
+        match self.furthest_position.cmp(&time_signature.measure_length()) {
+            Ordering::Less => MeasureFill::Incomplete,
+            Ordering::Equal => MeasureFill::Complete,
+            Ordering::Greater => MeasureFill::Overfull,
         }
-    } else {
-        Accidental::Natural
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::model::{NoteName, Pitch, pitch::Accidental};
+    use std::collections::HashSet;
+
+    use assert_float_eq::assert_float_absolute_eq;
+    use rstest::rstest;
+
+    use crate::{
+        analysis::{calculate_density_metrics, calculate_diversity_metrics},
+        extraction::loader::load_score_from_bytes,
+        model::{BeatGrouping, NoteName, Pitch, TupletUsage, pitch::Accidental},
+    };
 
     use super::*;
 
@@ -235,7 +1349,7 @@ mod tests {
         let score = create_empty_musicxml_dom();
 
         // Act
-        let actual_measure_data = extract_measure_data(&score);
+        let actual_measure_data = extract_measure_data(&score).unwrap();
 
         // Assert
         assert!(actual_measure_data.is_empty());
@@ -251,17 +1365,28 @@ mod tests {
         let score = create_musicxml_dom_with_two_quarter_notes();
 
         // Act
-        let actual = extract_measure_data(&score);
+        let actual = extract_measure_data(&score).unwrap();
 
         // Assert
         let expected = vec![MeasureData {
+            number: "1".to_string(),
             note_count: 2,
-            tempo_bpm: 120.0,                         // Default
+            onset_count: 2,
+            chord_sizes: Vec::new(),
+            grace_note_count: 0,
+            tuplets: TupletUsage::default(),
+            tempo: Tempo::quarter_notes(120.0),       // Default
             time_signature: TimeSignature::new(4, 4), // Default
+            length_quarters: 4.0,
             pitches: HashSet::from([
                 Pitch::new(NoteName::C, 4, Accidental::Natural),
                 Pitch::new(NoteName::D, 4, Accidental::Natural),
             ]),
+            sounding_pitches: HashSet::from([
+                Pitch::new(NoteName::C, 4, Accidental::Natural),
+                Pitch::new(NoteName::D, 4, Accidental::Natural),
+            ]),
+            percussion_instruments: HashSet::new(),
         }];
         assert_eq!(actual, expected);
     }
@@ -284,24 +1409,43 @@ mod tests {
         let score = create_musicxml_dom_with_two_measures();
 
         // Act
-        let actual = extract_measure_data(&score);
+        let actual = extract_measure_data(&score).unwrap();
 
         // Assert
         let expected = vec![
             MeasureData {
+                number: "1".to_string(),
                 note_count: 1,
-                tempo_bpm: 120.0,
+                onset_count: 1,
+                chord_sizes: Vec::new(),
+                grace_note_count: 0,
+                tuplets: TupletUsage::default(),
+                tempo: Tempo::quarter_notes(120.0),
                 time_signature: TimeSignature::new(4, 4),
+                length_quarters: 4.0,
                 pitches: HashSet::from([Pitch::new(NoteName::C, 4, Accidental::Natural)]),
+                sounding_pitches: HashSet::from([Pitch::new(NoteName::C, 4, Accidental::Natural)]),
+                percussion_instruments: HashSet::new(),
             },
             MeasureData {
+                number: "2".to_string(),
                 note_count: 2,
-                tempo_bpm: 120.0,
+                onset_count: 2,
+                chord_sizes: Vec::new(),
+                grace_note_count: 0,
+                tuplets: TupletUsage::default(),
+                tempo: Tempo::quarter_notes(120.0),
                 time_signature: TimeSignature::new(4, 4),
+                length_quarters: 2.0, // short final measure
                 pitches: HashSet::from([
                     Pitch::new(NoteName::D, 4, Accidental::Natural),
                     Pitch::new(NoteName::E, 4, Accidental::Natural),
                 ]),
+                sounding_pitches: HashSet::from([
+                    Pitch::new(NoteName::D, 4, Accidental::Natural),
+                    Pitch::new(NoteName::E, 4, Accidental::Natural),
+                ]),
+                percussion_instruments: HashSet::new(),
             },
         ];
         assert_eq!(actual, expected);
@@ -329,14 +1473,22 @@ mod tests {
         let score = create_musicxml_dom_with_60bpm_in_notation();
 
         // Act
-        let actual = extract_measure_data(&score);
+        let actual = extract_measure_data(&score).unwrap();
 
         // Assert
         let expected = vec![MeasureData {
+            number: "1".to_string(),
             note_count: 0,
-            tempo_bpm: 60.0,
+            onset_count: 0,
+            chord_sizes: Vec::new(),
+            grace_note_count: 0,
+            tuplets: TupletUsage::default(),
+            tempo: Tempo::quarter_notes(60.0),
             time_signature: TimeSignature::new(4, 4),
+            length_quarters: 4.0,
             pitches: HashSet::new(),
+            sounding_pitches: HashSet::new(),
+            percussion_instruments: HashSet::new(),
         }];
         assert_eq!(actual, expected);
     }
@@ -357,14 +1509,22 @@ mod tests {
         let score = create_score_with_60bpm_in_sound_tempo_and_120bpm_in_metronome();
 
         // Act
-        let actual = extract_measure_data(&score);
+        let actual = extract_measure_data(&score).unwrap();
 
         // Assert
         let expected = vec![MeasureData {
+            number: "1".to_string(),
             note_count: 0,
-            tempo_bpm: 60.0,
+            onset_count: 0,
+            chord_sizes: Vec::new(),
+            grace_note_count: 0,
+            tuplets: TupletUsage::default(),
+            tempo: Tempo::quarter_notes(60.0),
             time_signature: TimeSignature::new(4, 4),
+            length_quarters: 4.0,
             pitches: HashSet::new(),
+            sounding_pitches: HashSet::new(),
+            percussion_instruments: HashSet::new(),
         }];
         assert_eq!(actual, expected);
     }
@@ -395,12 +1555,12 @@ mod tests {
         let score = create_musicxml_dom_with_tempo_change();
 
         // Act
-        let result = extract_measure_data(&score);
+        let result = extract_measure_data(&score).unwrap();
 
         // Assert
         assert_eq!(result.len(), 2);
-        assert_eq!(result[0].tempo_bpm, 120.0);
-        assert_eq!(result[1].tempo_bpm, 60.0);
+        assert_eq!(result[0].tempo, Tempo::quarter_notes(120.0));
+        assert_eq!(result[1].tempo, Tempo::quarter_notes(60.0));
     }
 
     fn create_musicxml_dom_with_tempo_change() -> ScorePartwise {
@@ -423,7 +1583,7 @@ mod tests {
         let score = create_musicxml_dom_with_time_signature_change();
 
         // Act
-        let result = extract_measure_data(&score);
+        let result = extract_measure_data(&score).unwrap();
 
         // Assert
         assert_eq!(result.len(), 2);
@@ -451,14 +1611,22 @@ mod tests {
         let score = create_musicxml_dom_with_rest_only();
 
         // Act
-        let actual = extract_measure_data(&score);
+        let actual = extract_measure_data(&score).unwrap();
 
         // Assert
         let expected = vec![MeasureData {
+            number: "1".to_string(),
             note_count: 0,
-            tempo_bpm: 120.0,
+            onset_count: 0,
+            chord_sizes: Vec::new(),
+            grace_note_count: 0,
+            tuplets: TupletUsage::default(),
+            tempo: Tempo::quarter_notes(120.0),
             time_signature: TimeSignature::new(4, 4),
+            length_quarters: 4.0,
             pitches: HashSet::new(),
+            sounding_pitches: HashSet::new(),
+            percussion_instruments: HashSet::new(),
         }];
         assert_eq!(actual, expected);
     }
@@ -479,14 +1647,22 @@ mod tests {
         let score = create_musicxml_dom_with_half_note_metronome();
 
         // Act
-        let actual = extract_measure_data(&score);
+        let actual = extract_measure_data(&score).unwrap();
 
         // Assert
         let expected = vec![MeasureData {
+            number: "1".to_string(),
             note_count: 0,
-            tempo_bpm: 120.0,
+            onset_count: 0,
+            chord_sizes: Vec::new(),
+            grace_note_count: 0,
+            tuplets: TupletUsage::default(),
+            tempo: Tempo::new(60.0, 2.0),
             time_signature: TimeSignature::new(4, 4),
+            length_quarters: 4.0,
             pitches: HashSet::new(),
+            sounding_pitches: HashSet::new(),
+            percussion_instruments: HashSet::new(),
         }];
         assert_eq!(actual, expected);
     }
@@ -507,14 +1683,22 @@ mod tests {
         let score = create_musicxml_dom_with_eighth_note_metronome();
 
         // Act
-        let actual = extract_measure_data(&score);
+        let actual = extract_measure_data(&score).unwrap();
 
         // Assert
         let expected = vec![MeasureData {
+            number: "1".to_string(),
             note_count: 0,
-            tempo_bpm: 60.0, // 120 eighth notes per minute = 60 quarter notes per minute
+            onset_count: 0,
+            chord_sizes: Vec::new(),
+            grace_note_count: 0,
+            tuplets: TupletUsage::default(),
+            tempo: Tempo::new(120.0, 0.5), // 120 eighth notes per minute = 60 quarter notes per minute
             time_signature: TimeSignature::new(4, 4),
+            length_quarters: 4.0,
             pitches: HashSet::new(),
+            sounding_pitches: HashSet::new(),
+            percussion_instruments: HashSet::new(),
         }];
         assert_eq!(actual, expected);
     }
@@ -535,14 +1719,22 @@ mod tests {
         let score = create_musicxml_dom_with_dotted_half_note_metronome();
 
         // Act
-        let actual = extract_measure_data(&score);
+        let actual = extract_measure_data(&score).unwrap();
 
         // Assert
         let expected = vec![MeasureData {
+            number: "1".to_string(),
             note_count: 0,
-            tempo_bpm: 60.0 * 3.0,
+            onset_count: 0,
+            chord_sizes: Vec::new(),
+            grace_note_count: 0,
+            tuplets: TupletUsage::default(),
+            tempo: Tempo::new(60.0, 3.0),
             time_signature: TimeSignature::new(3, 4),
+            length_quarters: 3.0,
             pitches: HashSet::new(),
+            sounding_pitches: HashSet::new(),
+            percussion_instruments: HashSet::new(),
         }];
         assert_eq!(actual, expected);
     }
@@ -559,22 +1751,1872 @@ mod tests {
         create_test_score(&measures)
     }
 
-    fn create_test_score(measures: &str) -> ScorePartwise {
-        let xml = format!(
-            r#"<?xml version="1.0" encoding="UTF-8"?>
-<!DOCTYPE score-partwise PUBLIC "-//Recordare//DTD MusicXML 4.0 Partwise//EN" "http://www.musicxml.org/dtds/partwise.dtd">
-<score-partwise version="4.0">
-  <part-list>
-    <score-part id="P1">
-      <part-name>Test</part-name>
+    #[test]
+    fn test_extract_timeline_returns_onsets_and_durations() {
+        // Arrange
+        let score = create_musicxml_dom_with_divisions_and_two_measures();
+
+        // Act
+        let actual = extract_timeline(&score).unwrap();
+
+        // Assert
+        // 120 BPM = 0.5 seconds per quarter, 2 divisions per quarter = 0.25 seconds per division
+        assert_eq!(actual.measures.len(), 2);
+        assert_eq!(actual.measures[1].number, "2");
+        assert_float_absolute_eq!(actual.measures[1].start_seconds, 2.0);
+
+        let onsets: Vec<(RationalTime, RationalTime, usize)> = actual
+            .notes
+            .iter()
+            .map(|note| (note.onset, note.duration, note.measure_index))
+            .collect();
+        let eighths = |count| RationalTime::new(count, 8);
+        assert_eq!(
+            onsets,
+            vec![
+                (eighths(0), eighths(2), 0),
+                (eighths(2), eighths(1), 0),
+                (eighths(5), eighths(1), 0),
+                (eighths(0), eighths(8), 1)
+            ]
+        );
+
+        let seconds: Vec<(f64, f64)> = actual
+            .notes
+            .iter()
+            .map(|note| (note.onset_seconds, note.duration_seconds))
+            .collect();
+        assert_eq!(
+            seconds,
+            vec![(0.0, 0.5), (0.5, 0.25), (1.25, 0.25), (2.0, 2.0)]
+        );
+    }
+
+    fn create_musicxml_dom_with_divisions_and_two_measures() -> ScorePartwise {
+        let measures = format!(
+            r#"<measure number="1">
+    {divisions}
+    {note_c}
+    {note_d}
+    {rest}
+    {note_e}
+</measure>
+<measure number="2">
+    {note_f}
+</measure>"#,
+            divisions = create_divisions(2),
+            note_c = create_note_with_duration("C", 4, 2),
+            note_d = create_note_with_duration("D", 4, 1),
+            rest = create_rest_with_duration(2),
+            note_e = create_note_with_duration("E", 4, 1),
+            note_f = create_note_with_duration("F", 4, 8)
+        );
+        create_test_score(&measures)
+    }
+
+    #[test]
+    fn test_extract_timeline_marks_chord_members() {
+        // Arrange
+        let score = create_musicxml_dom_with_chord_followed_by_note();
+
+        // Act
+        let actual = extract_timeline(&score).unwrap();
+
+        // Assert
+        let chord_info: Vec<(NoteName, f64, bool)> = actual
+            .notes
+            .iter()
+            .map(|note| {
+                (
+                    note.written_pitch.note_name,
+                    note.onset.to_quarters(),
+                    note.is_chord_member,
+                )
+            })
+            .collect();
+        let expected = vec![
+            (NoteName::C, 0.0, true),
+            (NoteName::E, 0.0, true),
+            (NoteName::G, 0.0, true),
+            (NoteName::D, 1.0, false),
+        ];
+        assert_eq!(chord_info, expected);
+    }
+
+    fn create_musicxml_dom_with_chord_followed_by_note() -> ScorePartwise {
+        let measures = format!(
+            r#"<measure number="1">
+    {note_c}
+    {note_e}
+    {note_g}
+    {note_d}
+</measure>"#,
+            note_c = create_note("C", 4),
+            note_e = create_chord_note("E", 4),
+            note_g = create_chord_note("G", 4),
+            note_d = create_note("D", 4)
+        );
+        create_test_score(&measures)
+    }
+
+    #[test]
+    fn test_extract_timeline_reads_part_voice_and_staff() {
+        // Arrange
+        let score = create_musicxml_dom_with_voice_and_staff();
+
+        // Act
+        let actual = extract_timeline(&score).unwrap();
+
+        // Assert
+        assert_eq!(actual.notes.len(), 2);
+        assert_eq!(actual.notes[0].part_id, "P1");
+        assert_eq!(actual.notes[0].voice, Some("1".to_string()));
+        assert_eq!(actual.notes[0].staff, 1);
+        assert_eq!(actual.notes[1].voice, Some("5".to_string()));
+        assert_eq!(actual.notes[1].staff, 2);
+    }
+
+    fn create_musicxml_dom_with_voice_and_staff() -> ScorePartwise {
+        let measures = format!(
+            r#"<measure number="1">
+    {note_c}
+    {note_d}
+</measure>"#,
+            note_c = create_note_in_voice_and_staff("C", 5, "1", 1),
+            note_d = create_note_in_voice_and_staff("D", 3, "5", 2)
+        );
+        create_test_score(&measures)
+    }
+
+    #[test]
+    fn test_extract_timeline_places_second_voice_after_backup() {
+        // Arrange
+        let score = create_musicxml_dom_with_two_voices();
+
+        // Act
+        let actual = extract_timeline(&score).unwrap();
+
+        // Assert
+        let onsets: Vec<(NoteName, f64)> = actual
+            .notes
+            .iter()
+            .map(|note| (note.written_pitch.note_name, note.onset.to_quarters()))
+            .collect();
+        let expected = vec![
+            (NoteName::C, 0.0),
+            (NoteName::E, 0.0),
+            (NoteName::D, 1.0),
+            (NoteName::C, 2.0),
+            (NoteName::F, 2.0),
+            (NoteName::D, 3.0),
+        ];
+        assert_eq!(onsets, expected);
+        assert_eq!(actual.measures[0].parts[0].fill, MeasureFill::Complete);
+    }
+
+    fn create_musicxml_dom_with_two_voices() -> ScorePartwise {
+        let measures = format!(
+            r#"<measure number="1">
+    {note_c}
+    {note_d}
+    {note_c}
+    {note_d}
+    {backup}
+    {note_e}
+    {note_f}
+</measure>"#,
+            note_c = create_note("C", 5),
+            note_d = create_note("D", 5),
+            backup = create_backup(4),
+            note_e = create_note_with_duration("E", 3, 2),
+            note_f = create_note_with_duration("F", 3, 2)
+        );
+        create_test_score(&measures)
+    }
+
+    #[test]
+    fn test_extract_timeline_skips_time_for_forward() {
+        // Arrange
+        let score = create_musicxml_dom_with_forward();
+
+        // Act
+        let actual = extract_timeline(&score).unwrap();
+
+        // Assert
+        assert_eq!(actual.notes.len(), 1);
+        assert_eq!(actual.notes[0].onset, RationalTime::from_quarters(3));
+        assert_float_absolute_eq!(actual.notes[0].onset_seconds, 1.5);
+    }
+
+    fn create_musicxml_dom_with_forward() -> ScorePartwise {
+        let measures = format!(
+            r#"<measure number="1">
+    {forward}
+    {note_c}
+</measure>"#,
+            forward = create_forward(3),
+            note_c = create_note("C", 4)
+        );
+        create_test_score(&measures)
+    }
+
+    #[test]
+    fn test_extract_timeline_follows_divisions_change_between_measures() {
+        // Arrange
+        let score = create_musicxml_dom_with_divisions_change_between_measures();
+
+        // Act
+        let actual = extract_timeline(&score).unwrap();
+
+        // Assert
+        let seconds: Vec<(f64, f64)> = actual
+            .notes
+            .iter()
+            .map(|note| (note.onset_seconds, note.duration_seconds))
+            .collect();
+        assert_eq!(seconds, vec![(0.0, 2.0), (2.0, 0.5), (2.5, 1.5)]);
+        assert_eq!(actual.notes[1].duration, RationalTime::from_quarters(1));
+        assert!(
+            actual
+                .measures
+                .iter()
+                .all(|measure| measure.parts[0].fill == MeasureFill::Complete)
+        );
+    }
+
+    fn create_musicxml_dom_with_divisions_change_between_measures() -> ScorePartwise {
+        let measures = format!(
+            r#"<measure number="1">
+    {divisions_1}
+    {whole_note}
+</measure>
+<measure number="2">
+    {divisions_4}
+    {quarter_note}
+    {dotted_half_note}
+</measure>"#,
+            divisions_1 = create_divisions(1),
+            whole_note = create_note_with_duration("C", 4, 4),
+            divisions_4 = create_divisions(4),
+            quarter_note = create_note_with_duration("D", 4, 4),
+            dotted_half_note = create_note_with_duration("E", 4, 12)
+        );
+        create_test_score(&measures)
+    }
+
+    #[test]
+    fn test_extract_timeline_keeps_position_on_divisions_change_within_measure() {
+        // Arrange
+        let score = create_musicxml_dom_with_divisions_change_within_measure();
+
+        // Act
+        let actual = extract_timeline(&score).unwrap();
+
+        // Assert
+        assert_eq!(actual.notes.len(), 2);
+        assert_eq!(actual.notes[1].onset, RationalTime::from_quarters(1));
+        assert_eq!(actual.notes[1].duration, RationalTime::new(1, 8));
+        assert_float_absolute_eq!(actual.notes[1].onset_seconds, 0.5);
+        assert_float_absolute_eq!(actual.notes[1].duration_seconds, 0.25);
+    }
+
+    fn create_musicxml_dom_with_divisions_change_within_measure() -> ScorePartwise {
+        let measures = format!(
+            r#"<measure number="1">
+    {divisions_1}
+    {quarter_note}
+    {divisions_2}
+    {eighth_note}
+</measure>"#,
+            divisions_1 = create_divisions(1),
+            quarter_note = create_note_with_duration("C", 4, 1),
+            divisions_2 = create_divisions(2),
+            eighth_note = create_note_with_duration("D", 4, 1)
+        );
+        create_test_score(&measures)
+    }
+
+    #[test]
+    fn test_extract_timeline_detects_incomplete_and_overfull_measures() {
+        // Arrange
+        let score = create_musicxml_dom_with_incomplete_and_overfull_measures();
+
+        // Act
+        let actual = extract_timeline(&score).unwrap();
+
+        // Assert
+        let fills: Vec<MeasureFill> = actual.measures.iter().map(|m| m.parts[0].fill).collect();
+        let expected = vec![
+            MeasureFill::Incomplete,
+            MeasureFill::Complete,
+            MeasureFill::Overfull,
+        ];
+        assert_eq!(fills, expected);
+        assert_eq!(actual.irregular_measures().count(), 2);
+    }
+
+    fn create_musicxml_dom_with_incomplete_and_overfull_measures() -> ScorePartwise {
+        let measures = format!(
+            r#"<measure number="0">
+    {divisions}
+    {time_sig_3_8}
+    {eighth_note}
+</measure>
+<measure number="1">
+    {eighth_note}
+    {eighth_note}
+    {eighth_note}
+</measure>
+<measure number="2">
+    {dotted_quarter_note}
+    {eighth_note}
+</measure>"#,
+            divisions = create_divisions(2),
+            time_sig_3_8 = create_time_signature(3, 8),
+            eighth_note = create_note_with_duration("C", 4, 1),
+            dotted_quarter_note = create_note_with_duration("D", 4, 3)
+        );
+        create_test_score(&measures)
+    }
+
+    #[test]
+    fn test_extract_timeline_merges_parts_in_time() {
+        // Arrange
+        let score = create_musicxml_dom_with_two_parts();
+
+        // Act
+        let actual = extract_timeline(&score).unwrap();
+
+        // Assert
+        assert_eq!(actual.measures.len(), 2);
+        assert_eq!(actual.part_ids(), vec!["P1", "P2"]);
+        assert_float_absolute_eq!(actual.measures[1].start_seconds, 2.0);
+
+        let onsets: Vec<(&str, NoteName, f64)> = actual
+            .notes
+            .iter()
+            .map(|note| {
+                (
+                    note.part_id.as_str(),
+                    note.written_pitch.note_name,
+                    note.onset_seconds,
+                )
+            })
+            .collect();
+        let expected = vec![
+            ("P1", NoteName::C, 0.0),
+            ("P2", NoteName::E, 0.0),
+            ("P1", NoteName::C, 0.5),
+            ("P1", NoteName::C, 1.0),
+            ("P1", NoteName::C, 1.5),
+            ("P1", NoteName::D, 2.0),
+            ("P2", NoteName::F, 2.0),
+            ("P1", NoteName::D, 3.0),
+        ];
+        assert_eq!(onsets, expected);
+    }
+
+    #[test]
+    fn test_extract_measure_data_counts_all_parts_per_measure() {
+        // Arrange
+        let score = create_musicxml_dom_with_two_parts();
+
+        // Act
+        let actual: Vec<u32> = extract_measure_data(&score)
+            .unwrap()
+            .iter()
+            .map(|data| data.note_count)
+            .collect();
+
+        // Assert
+        assert_eq!(actual, vec![5, 3]);
+    }
+
+    fn create_musicxml_dom_with_two_parts() -> ScorePartwise {
+        let first_part = format!(
+            r#"<measure number="1">
+    {note_c}
+    {note_c}
+    {note_c}
+    {note_c}
+</measure>
+<measure number="2">
+    {half_note_d}
+    {half_note_d}
+</measure>"#,
+            note_c = create_note("C", 5),
+            half_note_d = create_note_with_duration("D", 5, 2)
+        );
+        let second_part = format!(
+            r#"<measure number="1">
+    {whole_note_e}
+</measure>
+<measure number="2">
+    {whole_note_f}
+</measure>"#,
+            whole_note_e = create_note_with_duration("E", 3, 4),
+            whole_note_f = create_note_with_duration("F", 3, 4)
+        );
+        create_test_score_with_parts(&[&first_part, &second_part])
+    }
+
+    #[test]
+    fn test_extract_timeline_aligns_parts_by_measure_number() {
+        // Arrange
+        let first_part = format!(
+            r#"<measure number="1">{note}</measure>
+<measure number="2">{note}</measure>"#,
+            note = create_note_with_duration("C", 4, 4)
+        );
+        let second_part = format!(
+            r#"<measure number="2">{note}</measure>"#,
+            note = create_note_with_duration("E", 4, 4)
+        );
+        let score = create_test_score_with_parts(&[&first_part, &second_part]);
+
+        // Act
+        let actual = extract_timeline(&score).unwrap();
+
+        // Assert
+        let parts_per_measure: Vec<Vec<&str>> = actual
+            .measures
+            .iter()
+            .map(|measure| measure.parts.iter().map(|p| p.part_id.as_str()).collect())
+            .collect();
+        assert_eq!(parts_per_measure, vec![vec!["P1"], vec!["P1", "P2"]]);
+        assert_eq!(actual.notes[2].part_id, "P2");
+        assert_eq!(actual.notes[2].measure_index, 1);
+        assert_float_absolute_eq!(actual.notes[2].onset_seconds, 2.0);
+    }
+
+    fn create_test_score_with_parts(parts: &[&str]) -> ScorePartwise {
+        let score_parts: String = (1..=parts.len())
+            .map(|i| {
+                format!(r#"<score-part id="P{i}"><part-name>Test {i}</part-name></score-part>"#)
+            })
+            .collect();
+        let part_elements: String = parts
+            .iter()
+            .enumerate()
+            .map(|(i, measures)| format!(r#"<part id="P{}">{measures}</part>"#, i + 1))
+            .collect();
+        let xml = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<score-partwise version="4.0">
+  <part-list>{score_parts}</part-list>
+  {part_elements}
+</score-partwise>"#
+        );
+        parse_musicxml_to_dom(&xml)
+    }
+
+    #[test]
+    fn test_extract_timeline_compound_meter_with_dotted_quarter_metronome() {
+        // Arrange
+        let score = create_musicxml_dom_with_6_8_at_60_dotted_quarters();
+
+        // Act
+        let actual = extract_timeline(&score).unwrap();
+
+        // Assert
+        // 60 dotted quarters per minute, two beats per 6/8 measure = 2 seconds per measure
+        assert_eq!(
+            actual.measures[0].time_signature.grouping,
+            BeatGrouping::Compound
+        );
+        assert_float_absolute_eq!(actual.measures[0].duration_seconds(), 2.0);
+        assert_float_absolute_eq!(actual.measures[1].start_seconds, 2.0);
+    }
+
+    fn create_musicxml_dom_with_6_8_at_60_dotted_quarters() -> ScorePartwise {
+        let measures = format!(
+            r#"<measure number="1">
+    {time_sig}
+    {metronome}
+</measure>
+<measure number="2">
+</measure>"#,
+            time_sig = create_time_signature(6, 8),
+            metronome = create_dotted_metronome("quarter", 60)
+        );
+        create_test_score(&measures)
+    }
+
+    #[test]
+    fn test_extract_measure_data_additive_time_signature() {
+        // Arrange
+        let measures = r#"<measure number="1">
+    <attributes>
+        <time>
+          <beats>3+2+2</beats>
+          <beat-type>8</beat-type>
+        </time>
+    </attributes>
+</measure>"#;
+        let score = create_test_score(measures);
+
+        // Act
+        let actual = extract_measure_data(&score).unwrap();
+
+        // Assert
+        assert_eq!(
+            actual[0].time_signature,
+            TimeSignature::additive(vec![3, 2, 2], 8)
+        );
+        // 7 eighths at 120 quarters per minute
+        assert_float_absolute_eq!(actual[0].get_measure_duration_seconds(), 1.75);
+    }
+
+    #[rstest]
+    #[case("0")]
+    #[case("3")]
+    #[case("x")]
+    fn test_extract_timeline_ignores_invalid_beat_type(#[case] beat_type: &str) {
+        // Arrange
+        let measures = format!(
+            r#"<measure number="1">
+    <attributes>
+        <time>
+          <beats>3</beats>
+          <beat-type>{beat_type}</beat-type>
+        </time>
+    </attributes>
+</measure>"#
+        );
+        let score = create_test_score(&measures);
+
+        // Act
+        let (timeline, _) = extract_timeline_lenient(&score);
+
+        // Assert
+        assert_eq!(
+            timeline.measures[0].time_signature,
+            TimeSignature::new(4, 4)
+        );
+        assert_eq!(timeline.measures[0].length, RationalTime::from_quarters(4));
+    }
+
+    #[rstest]
+    #[case("<beat-unit>whole</beat-unit><per-minute>30</per-minute>", 120.0)]
+    #[case("<beat-unit>breve</beat-unit><per-minute>15</per-minute>", 120.0)]
+    #[case("<beat-unit>maxima</beat-unit><per-minute>4</per-minute>", 128.0)]
+    #[case("<beat-unit>16th</beat-unit><per-minute>480</per-minute>", 120.0)]
+    #[case("<beat-unit>1024th</beat-unit><per-minute>25600</per-minute>", 100.0)]
+    #[case(
+        "<beat-unit>eighth</beat-unit><beat-unit-dot/><per-minute>160</per-minute>",
+        120.0
+    )]
+    #[case(
+        "<beat-unit>quarter</beat-unit><beat-unit-dot/><beat-unit-dot/><per-minute>80</per-minute>",
+        140.0
+    )]
+    #[case(
+        "<beat-unit>quarter</beat-unit><beat-unit-tied><beat-unit>eighth</beat-unit></beat-unit-tied><per-minute>60</per-minute>",
+        90.0
+    )]
+    fn test_extract_timeline_metronome_beat_units(
+        #[case] metronome_content: &str,
+        #[case] expected_quarter_notes_per_minute: f64,
+    ) {
+        // Arrange
+        let score = create_musicxml_dom_with_second_measure(&create_metronome_from_content(
+            metronome_content,
+        ));
+
+        // Act
+        let actual = extract_timeline(&score).unwrap();
+
+        // Assert
+        assert_float_absolute_eq!(
+            actual.measures[1].tempo.quarter_notes_per_minute(),
+            expected_quarter_notes_per_minute
+        );
+    }
+
+    fn create_metronome_from_content(content: &str) -> String {
+        format!(
+            r#"<direction placement="above">
+        <direction-type>
+          <metronome>{content}</metronome>
+        </direction-type>
+      </direction>"#
+        )
+    }
+
+    #[test]
+    fn test_extract_timeline_beat_equation_modulates_current_tempo() {
+        // Arrange
+        let measures = format!(
+            r#"<measure number="1">
+    {metronome}
+</measure>
+<measure number="2">
+    {modulation}
+</measure>
+<measure number="3">
+</measure>"#,
+            metronome = create_metronome("quarter", 120),
+            modulation = create_metronome_from_content(
+                "<beat-unit>quarter</beat-unit><beat-unit>quarter</beat-unit><beat-unit-dot/>"
+            )
+        );
+        let score = create_test_score(&measures);
+
+        // Act
+        let actual = extract_timeline(&score).unwrap();
+
+        // Assert
+        // The dotted quarter now lasts as long as the old quarter did
+        assert_eq!(actual.measures[1].tempo, Tempo::new(120.0, 1.5));
+        assert_eq!(actual.measures[2].tempo, Tempo::new(120.0, 1.5));
+        assert_float_absolute_eq!(actual.measures[1].duration_seconds(), 4.0 / 3.0);
+    }
+
+    #[rstest]
+    #[case(
+        create_metronome_from_content(METRONOME_BASED_CONTENT),
+        AnalysisError::UnsupportedMetronome(MeasureLocation::new("P1", "2"))
+    )]
+    #[case(
+        create_note_with_alter("C", 4, 4.0),
+        AnalysisError::UnsupportedAlter(MeasureLocation::new("P1", "2"), 400)
+    )]
+    fn test_extract_timeline_strict_returns_error_with_location(
+        #[case] content: String,
+        #[case] expected: AnalysisError,
+    ) {
+        // Arrange
+        let score = create_musicxml_dom_with_second_measure(&content);
+
+        // Act
+        let actual = extract_timeline(&score);
+
+        // Assert
+        assert_eq!(actual, Err(expected));
+    }
+
+    #[test]
+    fn test_extract_timeline_lenient_falls_back_and_records_warnings() {
+        // Arrange
+        let content = format!(
+            "{}{}",
+            create_metronome_from_content(METRONOME_BASED_CONTENT),
+            create_note_with_alter("C", 4, 4.0)
+        );
+        let score = create_musicxml_dom_with_second_measure(&content);
+
+        // Act
+        let (actual, warnings) = extract_timeline_lenient(&score);
+
+        // Assert
+        let location = MeasureLocation::new("P1", "2");
+        assert_eq!(
+            warnings,
+            vec![
+                AnalysisError::UnsupportedMetronome(location.clone()),
+                AnalysisError::UnsupportedAlter(location, 400),
+            ]
+        );
+        assert_eq!(actual.measures[1].tempo, Tempo::quarter_notes(120.0));
+        assert_eq!(
+            actual.notes[1].written_pitch,
+            Pitch::new(NoteName::C, 4, Accidental::Natural)
+        );
+    }
+
+    fn create_musicxml_dom_with_second_measure(content: &str) -> ScorePartwise {
+        let measures = format!(
+            r#"<measure number="1">
+    {}
+</measure>
+<measure number="2">
+    {content}
+</measure>"#,
+            create_note("C", 4)
+        );
+        create_test_score(&measures)
+    }
+
+    const METRONOME_BASED_CONTENT: &str = r#"<metronome-note>
+            <metronome-type>eighth</metronome-type>
+          </metronome-note>
+          <metronome-relation>equals</metronome-relation>
+          <metronome-note>
+            <metronome-type>eighth</metronome-type>
+            <metronome-dot/>
+          </metronome-note>"#;
+
+    fn create_note_with_alter(step: &str, octave: u8, alter: f64) -> String {
+        format!(
+            r#"<note>
+        <pitch>
+          <step>{step}</step>
+          <alter>{alter}</alter>
+          <octave>{octave}</octave>
+        </pitch>
+        <duration>1</duration>
+        <type>quarter</type>
+      </note>"#
+        )
+    }
+
+    #[rstest]
+    // <sound> between the second and third note
+    #[case("", create_sound_tempo(60))]
+    // Direction at the start of the measure, shifted to beat 3 by its offset
+    #[case(create_metronome_with_offset("quarter", 60, 2), String::new())]
+    fn test_extract_timeline_mid_measure_tempo_change(
+        #[case] measure_start: String,
+        #[case] after_second_note: String,
+    ) {
+        // Arrange
+        let measures = format!(
+            r#"<measure number="1">
+    {c}{d}{e}{f}
+</measure>
+<measure number="2">
+    {measure_start}{c}{d}{after_second_note}{e}{f}
+</measure>
+<measure number="3">
+    {c}
+</measure>"#,
+            c = create_note("C", 4),
+            d = create_note("D", 4),
+            e = create_note("E", 4),
+            f = create_note("F", 4),
+        );
+        let score = create_test_score(&measures);
+
+        // Act
+        let actual = extract_timeline(&score).unwrap();
+
+        // Assert
+        // Two quarters at 120 and two at 60
+        assert_float_absolute_eq!(actual.measures[1].start_seconds, 2.0);
+        assert_float_absolute_eq!(actual.measures[1].duration_seconds(), 3.0);
+        assert_float_absolute_eq!(actual.measures[2].start_seconds, 5.0);
+        let onsets: Vec<f64> = actual.notes[4..]
+            .iter()
+            .map(|note| note.onset_seconds)
+            .collect();
+        assert_eq!(onsets, vec![2.0, 2.5, 3.0, 4.0, 5.0]);
+        assert_float_absolute_eq!(actual.notes[6].duration_seconds, 1.0);
+    }
+
+    #[test]
+    fn test_extract_timeline_several_tempo_changes_in_one_measure() {
+        // Arrange
+        let measures = format!(
+            r#"<measure number="1">
+    {c}{tempo_60}{d}{tempo_30}{e}{f}
+</measure>"#,
+            c = create_note("C", 4),
+            d = create_note("D", 4),
+            e = create_note("E", 4),
+            f = create_note("F", 4),
+            tempo_60 = create_sound_tempo(60),
+            tempo_30 = create_sound_tempo(30),
+        );
+        let score = create_test_score(&measures);
+
+        // Act
+        let actual = extract_timeline(&score).unwrap();
+
+        // Assert
+        // The first tempo of the piece applies from its start
+        assert_eq!(actual.tempo_map.segments().len(), 2);
+        // Two quarters at 60 and two at 30
+        assert_float_absolute_eq!(actual.measures[0].duration_seconds(), 6.0);
+        assert_float_absolute_eq!(actual.notes[3].onset_seconds, 4.0);
+    }
+
+    fn create_sound_tempo(tempo: u32) -> String {
+        format!(r#"<sound tempo="{tempo}"/>"#)
+    }
+
+    fn create_metronome_with_offset(beat_unit: &str, per_minute: u32, offset: i32) -> String {
+        format!(
+            r#"<direction placement="above">
+        <direction-type>
+          <metronome>
+            <beat-unit>{beat_unit}</beat-unit>
+            <per-minute>{per_minute}</per-minute>
+          </metronome>
+        </direction-type>
+        <offset>{offset}</offset>
+      </direction>"#
+        )
+    }
+
+    #[test]
+    fn test_extract_timeline_ritardando_with_dashes_slows_down_until_dashes_stop() {
+        // Arrange
+        let four_notes = create_four_notes();
+        let measures = format!(
+            r#"<measure number="1">
+    {four_notes}
+</measure>
+<measure number="2">
+    {ritardando}{four_notes}
+</measure>
+<measure number="3">
+    {four_notes}
+</measure>
+<measure number="4">
+    {dashes_stop}{four_notes}
+</measure>"#,
+            ritardando = create_words_with_dashes("rit.", "start"),
+            dashes_stop = create_words_with_dashes("", "stop"),
+        );
+        let score = create_test_score(&measures);
+
+        // Act
+        let actual = extract_timeline(&score).unwrap();
+
+        // Assert
+        // Linear from 120 to 90 over eight quarters: 480 / -30 * ln(90 / 120) seconds
+        let ramp_seconds = -16.0 * 0.75_f64.ln();
+        assert_eq!(actual.tempo_map.tempo_at(8.0), Tempo::quarter_notes(105.0));
+        assert_eq!(actual.measures[3].tempo, Tempo::quarter_notes(90.0));
+        assert_float_absolute_eq!(actual.measures[3].start_seconds, 2.0 + ramp_seconds);
+        assert!(actual.measures[1].tempo.beats_per_minute < 120.0);
+    }
+
+    fn create_four_notes() -> String {
+        ["C", "D", "E", "F"]
+            .iter()
+            .map(|step| create_note(step, 4))
+            .collect()
+    }
+
+    fn create_words_with_dashes(words: &str, dashes_type: &str) -> String {
+        let words = if words.is_empty() {
+            String::new()
+        } else {
+            format!("<direction-type><words>{words}</words></direction-type>")
+        };
+        format!(
+            r#"<direction placement="above">
+        {words}
+        <direction-type>
+          <dashes type="{dashes_type}"/>
+        </direction-type>
+      </direction>"#
+        )
+    }
+
+    #[test]
+    fn test_extract_timeline_accelerando_without_dashes_lasts_through_next_measure() {
+        // Arrange
+        let four_notes = create_four_notes();
+        let measures = format!(
+            r#"<measure number="1">
+    {accelerando}{four_notes}
+</measure>
+<measure number="2">
+    {four_notes}
+</measure>
+<measure number="3">
+    {four_notes}
+</measure>"#,
+            accelerando = create_words("accel."),
+        );
+        let score = create_test_score(&measures);
+
+        // Act
+        let actual = extract_timeline(&score).unwrap();
+
+        // Assert
+        assert_eq!(actual.tempo_map.tempo_at(4.0), Tempo::quarter_notes(135.0));
+        assert_eq!(actual.measures[2].tempo, Tempo::quarter_notes(150.0));
+    }
+
+    fn create_words(words: &str) -> String {
+        format!(
+            r#"<direction placement="above">
+        <direction-type>
+          <words>{words}</words>
+        </direction-type>
+      </direction>"#
+        )
+    }
+
+    #[test]
+    fn test_extract_timeline_a_tempo_restores_tempo_before_ritardando() {
+        // Arrange
+        let four_notes = create_four_notes();
+        let measures = format!(
+            r#"<measure number="1">
+    {metronome}{four_notes}
+</measure>
+<measure number="2">
+    {ritardando}{four_notes}
+</measure>
+<measure number="3">
+    {a_tempo}{four_notes}
+</measure>"#,
+            metronome = create_metronome("quarter", 80),
+            ritardando = create_words("poco rit."),
+            a_tempo = create_words("a tempo"),
+        );
+        let score = create_test_score(&measures);
+
+        // Act
+        let actual = extract_timeline(&score).unwrap();
+
+        // Assert
+        // The ritardando is cut short by the next mark and never reaches 60
+        assert_eq!(actual.tempo_map.tempo_at(6.0), Tempo::quarter_notes(70.0));
+        assert_eq!(actual.measures[2].tempo, Tempo::quarter_notes(80.0));
+    }
+
+    #[rstest]
+    #[case("rit.", Some(TempoChange::Ramp(RampDirection::Slower)))]
+    #[case("poco a poco ritard.", Some(TempoChange::Ramp(RampDirection::Slower)))]
+    #[case("Rallentando", Some(TempoChange::Ramp(RampDirection::Slower)))]
+    #[case("allarg.", Some(TempoChange::Ramp(RampDirection::Slower)))]
+    #[case("slentando", Some(TempoChange::Ramp(RampDirection::Slower)))]
+    #[case("accel.", Some(TempoChange::Ramp(RampDirection::Faster)))]
+    #[case("stringendo", Some(TempoChange::Ramp(RampDirection::Faster)))]
+    #[case("a tempo", Some(TempoChange::ATempo))]
+    #[case("Tempo I", Some(TempoChange::TempoPrimo))]
+    #[case("tempo primo", Some(TempoChange::TempoPrimo))]
+    #[case("ritardando", Some(TempoChange::Ramp(RampDirection::Slower)))]
+    #[case("riten.", Some(TempoChange::Ramp(RampDirection::Slower)))]
+    #[case("ritenuto", Some(TempoChange::Ramp(RampDirection::Slower)))]
+    #[case("ritmico", None)]
+    #[case("ritornello", None)]
+    #[case("al ritorno", None)]
+    #[case("dolce", None)]
+    #[case("Allegro", Some(TempoChange::Term(138.0)))]
+    fn test_extract_tempo_change_from_words(
+        #[case] text: &str,
+        #[case] expected: Option<TempoChange>,
+    ) {
+        // Act
+        let actual = extract_tempo_change_from_words(text, &TempoTermTable::default());
+
+        // Assert
+        assert_eq!(actual, expected);
+    }
+
+    #[rstest]
+    #[case("4", "4", "Allegro", Tempo::quarter_notes(138.0))]
+    #[case("6", "8", "Andante", Tempo::new(92.0, 1.5))] // terms describe the beat
+    #[case("3+2+2", "8", "Allegro", Tempo::quarter_notes(138.0))] // not eighths
+    fn test_extract_timeline_tempo_term_without_metronome(
+        #[case] beats: &str,
+        #[case] beat_type: &str,
+        #[case] term: &str,
+        #[case] expected: Tempo,
+    ) {
+        // Arrange
+        let measures = format!(
+            r#"<measure number="1">
+    <attributes>
+        <time><beats>{beats}</beats><beat-type>{beat_type}</beat-type></time>
+    </attributes>
+    {words}{c}
+</measure>"#,
+            words = create_words(term),
+            c = create_note("C", 4),
+        );
+        let score = create_test_score(&measures);
+
+        // Act
+        let actual = extract_timeline(&score).unwrap();
+
+        // Assert
+        assert_eq!(actual.measures[0].tempo, expected);
+        assert_eq!(
+            actual.tempo_map.segments()[0].source,
+            TempoSource::Text(term.to_string())
+        );
+    }
+
+    #[test]
+    fn test_extract_timeline_metronome_wins_over_tempo_term() {
+        // Arrange
+        let measures = format!(
+            r#"<measure number="1">
+    {words}{metronome}{c}
+</measure>
+<measure number="2">
+    {presto}{c}
+</measure>"#,
+            words = create_words("Allegro"),
+            metronome = create_metronome("quarter", 100),
+            presto = create_words("Presto"),
+            c = create_note("C", 4),
+        );
+        let score = create_test_score(&measures);
+
+        // Act
+        let actual = extract_timeline(&score).unwrap();
+
+        // Assert
+        let sources: Vec<&TempoSource> = actual
+            .tempo_map
+            .segments()
+            .iter()
+            .map(|segment| &segment.source)
+            .collect();
+        assert_eq!(
+            sources,
+            vec![
+                &TempoSource::Metronome,
+                &TempoSource::Text("Presto".to_string())
+            ]
+        );
+        assert_eq!(actual.measures[0].tempo, Tempo::quarter_notes(100.0));
+        assert_eq!(actual.measures[1].tempo, Tempo::quarter_notes(184.0));
+    }
+
+    #[test]
+    fn test_extract_timeline_without_tempo_reports_default_source() {
+        // Arrange
+        let score = create_musicxml_dom_with_two_quarter_notes();
+
+        // Act
+        let actual = extract_timeline(&score).unwrap();
+
+        // Assert
+        assert_eq!(actual.tempo_map.segments()[0].source, TempoSource::Default);
+    }
+
+    #[rstest]
+    #[case(MeasureOrder::Written, vec!["1", "2", "3", "4"])]
+    #[case(MeasureOrder::Performance, vec!["1", "2", "1", "3", "4", "1"])]
+    fn test_extract_timeline_measure_order(
+        #[case] measure_order: MeasureOrder,
+        #[case] expected_numbers: Vec<&str>,
+    ) {
+        // Arrange
+        let score = create_musicxml_dom_with_repeat_endings_and_da_capo();
+        let options = ExtractionOptions {
+            measure_order,
+            ..ExtractionOptions::default()
+        };
+
+        // Act
+        let (actual, _) =
+            extract_timeline_with_options(&score, &MicrotonalAlters::default(), &options).unwrap();
+
+        // Assert
+        let actual_numbers: Vec<&str> = actual
+            .measures
+            .iter()
+            .map(|measure| measure.number.as_str())
+            .collect();
+        assert_eq!(actual_numbers, expected_numbers);
+        assert_eq!(actual.notes.len(), expected_numbers.len());
+        let last = actual.measures.last().unwrap();
+        assert_float_absolute_eq!(
+            last.start_seconds,
+            (expected_numbers.len() - 1) as f64 * 0.5
+        );
+    }
+
+    // |: 1 Fine |1. 2 :|2. 3 | 4 D.C. al Fine, one quarter note per measure
+    fn create_musicxml_dom_with_repeat_endings_and_da_capo() -> ScorePartwise {
+        let measures = format!(
+            r#"<measure number="1">
+    <attributes>
+        <time><beats>1</beats><beat-type>4</beat-type></time>
+    </attributes>
+    <barline location="left"><repeat direction="forward"/></barline>
+    {c}
+    {fine}
+</measure>
+<measure number="2">
+    <barline location="left"><ending number="1" type="start"/></barline>
+    {c}
+    <barline location="right">
+        <ending number="1" type="stop"/>
+        <repeat direction="backward"/>
+    </barline>
+</measure>
+<measure number="3">
+    <barline location="left"><ending number="2" type="start"/></barline>
+    {c}
+    <barline location="right"><ending number="2" type="stop"/></barline>
+</measure>
+<measure number="4">
+    {c}
+    {da_capo}
+</measure>"#,
+            c = create_note("C", 4),
+            fine = create_words("Fine"),
+            da_capo = r#"<direction><direction-type><words>D.C. al Fine</words></direction-type><sound dacapo="yes"/></direction>"#,
+        );
+        create_test_score(&measures)
+    }
+
+    #[test]
+    fn test_extract_timeline_pickup_lasts_as_long_as_its_content() {
+        // Arrange
+        let four_notes = create_four_notes();
+        let measures = format!(
+            r#"<measure number="0" implicit="yes">
+    {c}
+</measure>
+<measure number="1">
+    {four_notes}
+</measure>
+<measure number="2">
+    {c}{c}
+</measure>
+<measure number="3">
+    {four_notes}
+</measure>"#,
+            c = create_note("C", 4),
+        );
+        let score = create_test_score(&measures);
+
+        // Act
+        let actual = extract_timeline(&score).unwrap();
+
+        // Assert
+        let implicit: Vec<bool> = actual.measures.iter().map(|m| m.implicit).collect();
+        let lengths: Vec<f64> = actual
+            .measures
+            .iter()
+            .map(|m| m.length_in_quarters())
+            .collect();
+        assert_eq!(implicit, vec![true, false, false, false]);
+        // An incomplete measure in the middle keeps its length and is marked incomplete
+        assert_eq!(lengths, vec![1.0, 4.0, 4.0, 4.0]);
+        assert_float_absolute_eq!(actual.measures[1].start_seconds, 0.5);
+        assert_eq!(actual.measures[2].parts[0].fill, MeasureFill::Incomplete);
+    }
+
+    #[test]
+    fn test_extract_timeline_short_first_measure_without_implicit_keeps_its_length() {
+        // Arrange
+        let measures = format!(
+            r#"<measure number="1">
+    {c}
+</measure>
+<measure number="2">
+    {four_notes}
+</measure>"#,
+            c = create_note("C", 4),
+            four_notes = create_four_notes(),
+        );
+        let score = create_test_score(&measures);
+
+        // Act
+        let actual = extract_timeline(&score).unwrap();
+
+        // Assert
+        assert!(!actual.measures[0].implicit);
+        assert_eq!(actual.measures[0].length, RationalTime::from_quarters(4));
+        assert_eq!(actual.measures[0].parts[0].fill, MeasureFill::Incomplete);
+        assert_float_absolute_eq!(actual.measures[1].start_seconds, 2.0);
+    }
+
+    #[test]
+    fn test_extract_timeline_merges_notes_tied_across_barline() {
+        // Arrange
+        let measures = format!(
+            r#"<measure number="1">
+    {first}
+    {tie_start}
+</measure>
+<measure number="2">
+    {tie_stop}
+</measure>"#,
+            first = create_tied_note("C", 4, 2, &[], false),
+            tie_start = create_tied_note("C", 4, 2, &["start"], false),
+            tie_stop = create_tied_note("C", 4, 4, &["stop"], false),
+        );
+        let score = create_test_score(&measures);
+
+        // Act
+        let actual = extract_timeline(&score).unwrap();
+
+        // Assert
+        let durations: Vec<RationalTime> = actual.notes.iter().map(|note| note.duration).collect();
+        assert_eq!(
+            durations,
+            vec![
+                RationalTime::from_quarters(2),
+                RationalTime::from_quarters(6)
+            ]
+        );
+        // 6 quarters at 120 BPM
+        assert_float_absolute_eq!(actual.notes[1].duration_seconds, 3.0);
+        let note_counts: Vec<u32> = actual
+            .to_measure_data()
+            .iter()
+            .map(|data| data.note_count)
+            .collect();
+        assert_eq!(note_counts, vec![2, 0]);
+    }
+
+    fn create_tied_note(
+        step: &str,
+        octave: u8,
+        duration: u32,
+        tie_types: &[&str],
+        is_chord: bool,
+    ) -> String {
+        let chord = if is_chord { "<chord/>" } else { "" };
+        let ties: String = tie_types
+            .iter()
+            .map(|tie_type| format!(r#"<tie type="{tie_type}"/>"#))
+            .collect();
+        format!(
+            r#"<note>
+        {chord}
+        <pitch>
+          <step>{step}</step>
+          <octave>{octave}</octave>
+        </pitch>
+        <duration>{duration}</duration>
+        {ties}
+      </note>"#
+        )
+    }
+
+    #[test]
+    fn test_extract_timeline_merges_tied_chord_members() {
+        // Arrange
+        // C-E tied over to C-E-G, then a C that continues the C again
+        let measures = format!(
+            r#"<measure number="1">
+    {c_start}
+    {e_start}
+</measure>
+<measure number="2">
+    {c_continue}
+    {e_stop}
+    {g}
+</measure>
+<measure number="3">
+    {c_stop}
+</measure>"#,
+            c_start = create_tied_note("C", 4, 4, &["start"], false),
+            e_start = create_tied_note("E", 4, 4, &["start"], true),
+            c_continue = create_tied_note("C", 4, 4, &["stop", "start"], false),
+            e_stop = create_tied_note("E", 4, 4, &["stop"], true),
+            g = create_tied_note("G", 4, 4, &[], true),
+            c_stop = create_tied_note("C", 4, 4, &["stop"], false),
+        );
+        let score = create_test_score(&measures);
+
+        // Act
+        let actual = extract_timeline(&score).unwrap();
+
+        // Assert
+        let notes: Vec<(NoteName, usize, RationalTime)> = actual
+            .notes
+            .iter()
+            .map(|note| {
+                (
+                    note.written_pitch.note_name,
+                    note.measure_index,
+                    note.duration,
+                )
+            })
+            .collect();
+        assert_eq!(
+            notes,
+            vec![
+                (NoteName::C, 0, RationalTime::from_quarters(12)),
+                (NoteName::E, 0, RationalTime::from_quarters(8)),
+                (NoteName::G, 1, RationalTime::from_quarters(4))
+            ]
+        );
+        let measure_data = actual.to_measure_data();
+        assert_eq!(measure_data[0].chord_sizes, vec![2]);
+        // The G is the only new note of its chord
+        assert!(measure_data[1].chord_sizes.is_empty());
+    }
+
+    #[test]
+    fn test_extract_timeline_keeps_grace_and_cue_notes_apart() {
+        // Arrange
+        let measures = format!(
+            r#"<measure number="1">
+    {grace_d}
+    {c}
+    {cue_e}
+    {grace_f}
+    {grace_a}
+    {g}
+    {rest}
+</measure>"#,
+            grace_d = create_grace_note("D", r#"slash="yes" steal-time-previous="10""#, false),
+            c = create_note("C", 4),
+            cue_e = r#"<note>
+        <cue/>
+        <pitch>
+          <step>E</step>
+          <octave>4</octave>
+        </pitch>
+        <duration>1</duration>
+      </note>"#,
+            grace_f = create_grace_note("F", "", false),
+            grace_a = create_grace_note("A", "", true),
+            g = create_note("G", 4),
+            rest = create_rest(),
+        );
+        let score = create_test_score(&measures);
+
+        // Act
+        let actual = extract_timeline(&score).unwrap();
+
+        // Assert
+        let notes: Vec<(NoteName, f64, NoteKind)> = actual
+            .notes
+            .iter()
+            .map(|note| {
+                (
+                    note.written_pitch.note_name,
+                    note.onset.to_quarters(),
+                    note.kind,
+                )
+            })
+            .collect();
+        let acciaccatura = NoteKind::Grace(GraceNote {
+            slash: true,
+            steal_time_previous: Some(10.0),
+            ..GraceNote::default()
+        });
+        let grace = NoteKind::Grace(GraceNote::default());
+        assert_eq!(
+            notes,
+            vec![
+                (NoteName::D, 0.0, acciaccatura),
+                (NoteName::C, 0.0, NoteKind::Regular),
+                (NoteName::E, 1.0, NoteKind::Cue),
+                (NoteName::F, 2.0, grace),
+                (NoteName::A, 2.0, grace),
+                (NoteName::G, 2.0, NoteKind::Regular),
+            ]
+        );
+        let measure_data = actual.to_measure_data();
+        assert_eq!(measure_data[0].note_count, 2);
+        assert_eq!(measure_data[0].onset_count, 2);
+        assert_eq!(measure_data[0].grace_note_count, 3);
+        assert!(measure_data[0].chord_sizes.is_empty());
+        // The cue note is not played, the grace notes are
+        assert_eq!(measure_data[0].pitches.len(), 5);
+        assert_eq!(actual.measures[0].parts[0].fill, MeasureFill::Complete);
+    }
+
+    fn create_grace_note(step: &str, grace_attributes: &str, is_chord: bool) -> String {
+        let chord = if is_chord { "<chord/>" } else { "" };
+        format!(
+            r#"<note>
+        <grace {grace_attributes}/>
+        {chord}
+        <pitch>
+          <step>{step}</step>
+          <octave>4</octave>
+        </pitch>
+        <type>eighth</type>
+      </note>"#
+        )
+    }
+
+    #[test]
+    fn test_extract_timeline_reads_nested_tuplets() {
+        // Arrange
+        // A quarter note triplet whose middle note is an eighth note triplet,
+        // then a triplet eighth without a bracket and a plain eighth
+        let measures = format!(
+            r#"<measure number="1">
+    {divisions}
+    {outer_start}
+    {inner_start}
+    {inner}
+    {inner_stop}
+    {outer_stop}
+    {unbracketed}
+    {plain}
+</measure>"#,
+            divisions = create_divisions(9),
+            outer_start = create_tuplet_note(6, Some((3, 2)), "1", r#"<tuplet type="start"/>"#),
+            inner_start =
+                create_tuplet_note(2, Some((9, 4)), "1", r#"<tuplet type="start" number="2"/>"#),
+            inner = create_tuplet_note(2, Some((9, 4)), "1", ""),
+            inner_stop =
+                create_tuplet_note(2, Some((9, 4)), "1", r#"<tuplet type="stop" number="2"/>"#),
+            outer_stop = create_tuplet_note(6, Some((3, 2)), "1", r#"<tuplet type="stop"/>"#),
+            unbracketed = create_tuplet_note(3, Some((3, 2)), "1", ""),
+            plain = create_tuplet_note(9, None, "1", ""),
+        );
+        let score = create_test_score(&measures);
+
+        // Act
+        let actual = extract_timeline(&score).unwrap();
+
+        // Assert
+        let triplet = TupletRatio::new(3, 2);
+        let tuplets: Vec<Vec<TupletRatio>> = actual
+            .notes
+            .iter()
+            .map(|note| note.tuplets.clone())
+            .collect();
+        assert_eq!(
+            tuplets,
+            vec![
+                vec![triplet],
+                vec![triplet, triplet],
+                vec![triplet, triplet],
+                vec![triplet, triplet],
+                vec![triplet],
+                vec![triplet],
+                vec![],
+            ]
+        );
+        let usage = &actual.to_measure_data()[0].tuplets;
+        assert_eq!(usage.note_count, 6);
+        assert_eq!(usage.nested_note_count, 3);
+        assert!(!usage.cross_rhythm);
+    }
+
+    fn create_tuplet_note(
+        duration: u32,
+        time_modification: Option<(u32, u32)>,
+        voice: &str,
+        tuplet_notations: &str,
+    ) -> String {
+        let time_modification = time_modification.map_or(String::new(), |(actual, normal)| {
+            format!(
+                r#"<time-modification>
+          <actual-notes>{actual}</actual-notes>
+          <normal-notes>{normal}</normal-notes>
+        </time-modification>"#
+            )
+        });
+        let notations = if tuplet_notations.is_empty() {
+            String::new()
+        } else {
+            format!("<notations>{tuplet_notations}</notations>")
+        };
+        format!(
+            r#"<note>
+        <pitch>
+          <step>C</step>
+          <octave>4</octave>
+        </pitch>
+        <duration>{duration}</duration>
+        <voice>{voice}</voice>
+        {time_modification}
+        {notations}
+      </note>"#
+        )
+    }
+
+    #[rstest]
+    // Sixteenths start inside the triplet eighths: 3 against 4
+    #[case(4, 3, true)]
+    // A quarter note only starts together with the triplet
+    #[case(1, 12, false)]
+    fn test_extract_timeline_detects_cross_rhythm_between_voices(
+        #[case] second_voice_notes: usize,
+        #[case] second_voice_duration: u32,
+        #[case] expected: bool,
+    ) {
+        // Arrange
+        let triplet_eighth = create_tuplet_note(4, Some((3, 2)), "1", "").repeat(3);
+        let second_voice =
+            create_tuplet_note(second_voice_duration, None, "2", "").repeat(second_voice_notes);
+        let measures = format!(
+            r#"<measure number="1">
+    {divisions}
+    {triplet_eighth}
+    {backup}
+    {second_voice}
+</measure>"#,
+            divisions = create_divisions(12),
+            backup = create_backup(12),
+        );
+        let score = create_test_score(&measures);
+
+        // Act
+        let actual = extract_timeline(&score).unwrap();
+
+        // Assert
+        let usage = &actual.to_measure_data()[0].tuplets;
+        assert_eq!(usage.cross_rhythm, expected);
+        assert_eq!(usage.ratios, HashSet::from([TupletRatio::new(3, 2)]));
+    }
+
+    #[test]
+    fn test_extract_timeline_reads_unpitched_notes() {
+        // Arrange
+        let measures = format!(
+            r#"<measure number="1">
+    {snare}
+    {hi_hat}
+    {kick}
+    {snare}
+</measure>"#,
+            snare = create_unpitched_note("C", 5, Some("P1-I39")),
+            hi_hat = create_unpitched_note("G", 5, Some("P1-I43")),
+            kick = create_unpitched_note("F", 4, None),
+        );
+        let score = create_test_score(&measures);
+
+        // Act
+        let actual = extract_timeline(&score).unwrap();
+
+        // Assert
+        assert_eq!(actual.notes.len(), 4);
+        assert!(actual.notes[0].is_unpitched);
+        assert_eq!(
+            actual.notes[0].written_pitch,
+            Pitch::new(NoteName::C, 5, Accidental::Natural)
+        );
+        assert_eq!(actual.notes[0].instrument_id, Some("P1-I39".to_string()));
+        let measure_data = actual.to_measure_data();
+        assert_eq!(measure_data[0].note_count, 4);
+        assert!(measure_data[0].pitches.is_empty());
+        assert_eq!(measure_data[0].percussion_instruments.len(), 3);
+    }
+
+    fn create_unpitched_note(step: &str, octave: u8, instrument_id: Option<&str>) -> String {
+        let instrument =
+            instrument_id.map_or(String::new(), |id| format!(r#"<instrument id="{id}"/>"#));
+        format!(
+            r#"<note>
+        <unpitched>
+          <display-step>{step}</display-step>
+          <display-octave>{octave}</display-octave>
+        </unpitched>
+        <duration>1</duration>
+        {instrument}
+        <type>quarter</type>
+      </note>"#
+        )
+    }
+
+    #[test]
+    fn test_extract_timeline_keeps_microtonal_and_triple_alters() {
+        // Arrange
+        let measures = format!(
+            r#"<measure number="1">
+    {}
+    {}
+    {}
+</measure>"#,
+            create_note_with_alter("E", 4, -0.5),
+            create_note_with_alter("F", 4, 1.5),
+            create_note_with_alter("G", 4, 3.0),
+        );
+        let loaded = load_score_from_bytes(create_test_xml(&measures).into_bytes()).unwrap();
+
+        // Act
+        let (actual, _) = extract_timeline_with_options(
+            &loaded.score,
+            &loaded.microtonal_alters,
+            &ExtractionOptions::default(),
+        )
+        .unwrap();
+
+        // Assert
+        let accidentals: Vec<Accidental> = actual
+            .notes
+            .iter()
+            .map(|note| note.written_pitch.accidental)
+            .collect();
+        assert_eq!(
+            accidentals,
+            vec![
+                Accidental::QuarterFlat,
+                Accidental::ThreeQuarterSharp,
+                Accidental::TripleSharp
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_timeline_reads_alters_of_score_parsed_by_musicxml_crate() {
+        // Arrange
+        let measures = format!(
+            r#"<measure number="1">
+    {}
+    {}
+</measure>"#,
+            create_note_with_alter("B", 4, -1.0),
+            create_note_with_alter("F", 4, 2.0),
+        );
+        let score =
+            musicxml::read_score_data_partwise(create_test_xml(&measures).into_bytes()).unwrap();
+
+        // Act
+        let actual = extract_timeline(&score).unwrap();
+
+        // Assert
+        let accidentals: Vec<Accidental> = actual
+            .notes
+            .iter()
+            .map(|note| note.written_pitch.accidental)
+            .collect();
+        assert_eq!(accidentals, vec![Accidental::Flat, Accidental::DoubleSharp]);
+    }
+
+    #[test]
+    fn test_extract_timeline_keeps_microtonal_key_alter() {
+        // Arrange
+        let measures = format!(
+            r#"<measure number="1">
+    <attributes>
+        <key><key-step>B</key-step><key-alter>-0.5</key-alter></key>
+    </attributes>
+    {}
+</measure>"#,
+            create_note("C", 4),
+        );
+        let loaded = load_score_from_bytes(create_test_xml(&measures).into_bytes()).unwrap();
+
+        // Act
+        let (actual, _) = extract_timeline_with_options(
+            &loaded.score,
+            &loaded.microtonal_alters,
+            &ExtractionOptions::default(),
+        )
+        .unwrap();
+
+        // Assert
+        assert_eq!(
+            actual.measures[0].parts[0].key_changes[0].key,
+            KeySignature::NonTraditional(vec![(NoteName::B, Accidental::QuarterFlat)])
+        );
+    }
+
+    #[test]
+    fn test_extract_timeline_applies_transpose_to_sounding_pitch() {
+        // Arrange
+        // Clarinet in B flat, then guitar an octave down doubled at written pitch
+        let measures = format!(
+            r#"<measure number="1">
+    {}
+    {}
+</measure>
+<measure number="2">
+    {}
+    {}
+</measure>"#,
+            create_transpose("<diatonic>-1</diatonic><chromatic>-2</chromatic>"),
+            create_note("D", 5),
+            create_transpose(
+                r#"<chromatic>0</chromatic><octave-change>-1</octave-change><double above="yes"/>"#
+            ),
+            create_note("E", 4),
+        );
+        let score = create_test_score(&measures);
+
+        // Act
+        let actual = extract_timeline(&score).unwrap();
+
+        // Assert
+        let pitches: Vec<(Pitch, Pitch, Option<Pitch>)> = actual
+            .notes
+            .iter()
+            .map(|note| (note.written_pitch, note.sounding_pitch, note.doubled_pitch))
+            .collect();
+        assert_eq!(
+            pitches,
+            vec![
+                (
+                    Pitch::new(NoteName::D, 5, Accidental::Natural),
+                    Pitch::new(NoteName::C, 5, Accidental::Natural),
+                    None
+                ),
+                (
+                    Pitch::new(NoteName::E, 4, Accidental::Natural),
+                    Pitch::new(NoteName::E, 3, Accidental::Natural),
+                    Some(Pitch::new(NoteName::E, 4, Accidental::Natural))
+                ),
+            ]
+        );
+        let measure_data = actual.to_measure_data();
+        assert_eq!(
+            measure_data[0].pitches,
+            HashSet::from([Pitch::new(NoteName::D, 5, Accidental::Natural)])
+        );
+        assert_eq!(
+            measure_data[0].sounding_pitches,
+            HashSet::from([Pitch::new(NoteName::C, 5, Accidental::Natural)])
+        );
+        assert_eq!(
+            measure_data[1].sounding_pitches,
+            HashSet::from([
+                Pitch::new(NoteName::E, 3, Accidental::Natural),
+                Pitch::new(NoteName::E, 4, Accidental::Natural),
+            ])
+        );
+    }
+
+    fn create_transpose(content: &str) -> String {
+        format!(
+            r#"<attributes>
+        <transpose>{content}</transpose>
+      </attributes>"#
+        )
+    }
+
+    #[test]
+    fn test_diversity_metrics_count_keys_of_transposing_part_in_concert_pitch() {
+        // Arrange: a B flat clarinet writes D5 to sound C5, then plays C5 untransposed
+        let measures = format!(
+            r#"<measure number="1">
+    {}
+    {}
+</measure>
+<measure number="2">
+    {}
+    {}
+</measure>"#,
+            create_transpose("<diatonic>-1</diatonic><chromatic>-2</chromatic>"),
+            create_note("D", 5),
+            create_transpose("<chromatic>0</chromatic>"),
+            create_note("C", 5),
+        );
+        let score = create_test_score(&measures);
+
+        // Act
+        let measure_data = extract_measure_data(&score).unwrap();
+        let actual = calculate_diversity_metrics(&measure_data);
+
+        // Assert: two written pitches, but both sound on the C5 key
+        assert_eq!(actual.total_unique_pitches, 2);
+        assert_eq!(actual.total_unique_keys, 1);
+    }
+
+    #[test]
+    fn test_extract_timeline_reads_key_and_clef_changes_per_staff() {
+        // Arrange
+        let measures = format!(
+            r#"<measure number="1">
+    <attributes>
+        <key><fifths>-3</fifths><mode>minor</mode></key>
+        <staves>2</staves>
+        <clef number="1"><sign>G</sign><line>2</line></clef>
+        <clef number="2"><sign>F</sign><line>4</line></clef>
+    </attributes>
+    {note}
+    {note}
+</measure>
+<measure number="2">
+    {note}
+    <attributes>
+        <key number="2"><key-step>B</key-step><key-alter>-1</key-alter></key>
+        <clef number="2"><sign>G</sign><line>2</line><clef-octave-change>-1</clef-octave-change></clef>
+    </attributes>
+    {note}
+</measure>"#,
+            note = create_note("C", 4),
+        );
+        let score = create_test_score(&measures);
+
+        // Act
+        let actual = extract_timeline(&score).unwrap();
+
+        // Assert
+        let first = &actual.measures[0].parts[0];
+        assert_eq!(
+            first.key_changes,
+            vec![KeyChange {
+                staff: None,
+                position_quarters: 0.0,
+                key: KeySignature::traditional(-3, Some(KeyMode::Minor)),
+            }]
+        );
+        assert_eq!(
+            first.clef_changes,
+            vec![
+                ClefChange {
+                    staff: 1,
+                    position_quarters: 0.0,
+                    clef: Clef::treble(),
+                },
+                ClefChange {
+                    staff: 2,
+                    position_quarters: 0.0,
+                    clef: Clef::bass(),
+                },
+            ]
+        );
+        let second = &actual.measures[1].parts[0];
+        assert_eq!(
+            second.key_changes,
+            vec![KeyChange {
+                staff: Some(2),
+                position_quarters: 1.0,
+                key: KeySignature::NonTraditional(vec![(NoteName::B, Accidental::Flat)]),
+            }]
+        );
+        assert_eq!(
+            second.clef_changes,
+            vec![ClefChange {
+                staff: 2,
+                position_quarters: 1.0,
+                clef: Clef::new(ClefSign::G, Some(2), -1),
+            }]
+        );
+    }
+
+    #[rstest]
+    #[case(MeasureOrder::Written)]
+    #[case(MeasureOrder::Performance)]
+    fn test_density_peak_measure_is_written_measure_number(#[case] measure_order: MeasureOrder) {
+        // Arrange: pickup 0 |: 1 | 2 :| with measure 2 the densest
+        let measures = format!(
+            r#"<measure number="0" implicit="yes">
+    {c}
+</measure>
+<measure number="1">
+    <barline location="left"><repeat direction="forward"/></barline>
+    {four_notes}
+</measure>
+<measure number="2">
+    {c}{e}{c}{c}{c}
+    <barline location="right"><repeat direction="backward"/></barline>
+</measure>"#,
+            c = create_note("C", 4),
+            e = create_chord_note("E", 4),
+            four_notes = create_four_notes(),
+        );
+        let score = create_test_score(&measures);
+        let options = ExtractionOptions {
+            measure_order,
+            ..ExtractionOptions::default()
+        };
+
+        // Act
+        let (timeline, _) =
+            extract_timeline_with_options(&score, &MicrotonalAlters::default(), &options).unwrap();
+        let actual = calculate_density_metrics(&timeline.to_measure_data());
+
+        // Assert
+        assert_eq!(actual.peak_measure.as_deref(), Some("2"));
+    }
+
+    fn create_test_score(measures: &str) -> ScorePartwise {
+        parse_musicxml_to_dom(&create_test_xml(measures))
+    }
+
+    fn create_test_xml(measures: &str) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE score-partwise PUBLIC "-//Recordare//DTD MusicXML 4.0 Partwise//EN" "http://www.musicxml.org/dtds/partwise.dtd">
+<score-partwise version="4.0">
+  <part-list>
+    <score-part id="P1">
+      <part-name>Test</part-name>
     </score-part>
   </part-list>
   <part id="P1">
     {measures}
   </part>
 </score-partwise>"#
-        );
-        parse_musicxml_to_dom(&xml)
+        )
     }
 
     fn create_note(step: &str, octave: u8) -> String {
@@ -590,6 +3632,80 @@ mod tests {
         )
     }
 
+    fn create_note_with_duration(step: &str, octave: u8, duration: u32) -> String {
+        format!(
+            r#"<note>
+        <pitch>
+          <step>{step}</step>
+          <octave>{octave}</octave>
+        </pitch>
+        <duration>{duration}</duration>
+      </note>"#
+        )
+    }
+
+    fn create_chord_note(step: &str, octave: u8) -> String {
+        format!(
+            r#"<note>
+        <chord/>
+        <pitch>
+          <step>{step}</step>
+          <octave>{octave}</octave>
+        </pitch>
+        <duration>1</duration>
+        <type>quarter</type>
+      </note>"#
+        )
+    }
+
+    fn create_note_in_voice_and_staff(step: &str, octave: u8, voice: &str, staff: u32) -> String {
+        format!(
+            r#"<note>
+        <pitch>
+          <step>{step}</step>
+          <octave>{octave}</octave>
+        </pitch>
+        <duration>1</duration>
+        <voice>{voice}</voice>
+        <type>quarter</type>
+        <staff>{staff}</staff>
+      </note>"#
+        )
+    }
+
+    fn create_rest_with_duration(duration: u32) -> String {
+        format!(
+            r#"<note>
+        <rest/>
+        <duration>{duration}</duration>
+      </note>"#
+        )
+    }
+
+    fn create_divisions(divisions: u32) -> String {
+        format!(
+            r#"<attributes>
+        <divisions>{divisions}</divisions>
+      </attributes>"#
+        )
+    }
+
+    fn create_backup(duration: u32) -> String {
+        format!(
+            r#"<backup>
+        <duration>{duration}</duration>
+      </backup>"#
+        )
+    }
+
+    fn create_forward(duration: u32) -> String {
+        format!(
+            r#"<forward>
+        <duration>{duration}</duration>
+      </forward>"#
+        )
+    }
+
     fn create_rest() -> String {
         r#"<note>
         <rest/>
@@ -638,7 +3754,8 @@ mod tests {
     }
 
     fn parse_musicxml_to_dom(xml: &str) -> ScorePartwise {
-        musicxml::read_score_data_partwise(xml.as_bytes().to_vec())
+        load_score_from_bytes(xml.as_bytes().to_vec())
             .expect("Failed to parse test XML")
+            .score
     }
 }
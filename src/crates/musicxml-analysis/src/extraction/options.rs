@@ -0,0 +1,33 @@
+use crate::{extraction::tempo_terms::TempoTermTable, model::RampShape};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MeasureOrder {
+    #[default]
+    Written, // every measure once, as printed
+    Performance, // repeats, endings and jumps unrolled as played
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ExtractionOptions {
+    pub lenient: bool, // record unsupported notation as warnings instead of failing
+    pub measure_order: MeasureOrder,
+    pub tempo_ramp: TempoRampOptions,
+    pub tempo_terms: TempoTermTable, // used where the score gives no metronome or sound tempo
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TempoRampOptions {
+    pub shape: RampShape,
+    pub slower_factor: f64, // tempo reached by "rit." relative to the tempo before it
+    pub faster_factor: f64, // tempo reached by "accel." relative to the tempo before it
+}
+
+impl Default for TempoRampOptions {
+    fn default() -> Self {
+        Self {
+            shape: RampShape::Linear,
+            slower_factor: 0.75,
+            faster_factor: 1.25,
+        }
+    }
+}
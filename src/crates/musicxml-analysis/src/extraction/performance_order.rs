@@ -0,0 +1,385 @@
+use musicxml::{
+    datatypes::{BackwardForward, StartStopDiscontinue, YesNo},
+    elements::{Barline, Direction, DirectionTypeContents, Measure, MeasureElement, Sound},
+};
+
+const DEFAULT_REPEAT_TIMES: u32 = 2;
+
+// Guards against scores whose jumps would loop forever
+const MAX_PLAYS_PER_MEASURE: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Jump {
+    DaCapo,   // back to the first measure
+    DalSegno, // back to the measure with the segno
+}
+
+// Repeat and jump markings of one written measure, from all parts together
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MeasureNavigation {
+    pub forward_repeat: bool,
+    pub backward_repeat: Option<u32>, // times the repeated section is played
+    pub ending_start: Option<Vec<u32>>, // passes that play the ending starting here
+    pub ending_end: bool,             // an ending stops or discontinues after this measure
+    pub segno: bool,
+    pub coda: bool,
+    pub to_coda: bool,
+    pub fine: bool,
+    pub jump: Option<Jump>,
+}
+
+impl MeasureNavigation {
+    pub fn from_measure(measure: &Measure) -> Self {
+        let mut navigation = Self::default();
+        for measure_content in &measure.content {
+            match measure_content {
+                MeasureElement::Barline(barline) => navigation.add_barline(barline),
+                MeasureElement::Direction(direction) => navigation.add_direction(direction),
+                MeasureElement::Sound(sound) => navigation.add_sound(sound),
+                _ => {}
+            }
+        }
+        navigation
+    }
+
+    pub fn merge(&mut self, other: &MeasureNavigation) {
+        self.forward_repeat |= other.forward_repeat;
+        self.backward_repeat = self.backward_repeat.or(other.backward_repeat);
+        if self.ending_start.is_none() {
+            self.ending_start = other.ending_start.clone();
+        }
+        self.ending_end |= other.ending_end;
+        self.segno |= other.segno;
+        self.coda |= other.coda;
+        self.to_coda |= other.to_coda;
+        self.fine |= other.fine;
+        self.jump = self.jump.or(other.jump);
+    }
+
+    fn add_barline(&mut self, barline: &Barline) {
+        if let Some(repeat) = &barline.content.repeat {
+            match repeat.attributes.direction {
+                BackwardForward::Forward => self.forward_repeat = true,
+                BackwardForward::Backward => {
+                    let times = repeat.attributes.times.as_ref().map(|times| **times);
+                    self.backward_repeat = Some(times.unwrap_or(DEFAULT_REPEAT_TIMES));
+                }
+            }
+        }
+
+        if let Some(ending) = &barline.content.ending {
+            match ending.attributes.r#type {
+                StartStopDiscontinue::Start => {
+                    let passes: Vec<u32> = ending
+                        .attributes
+                        .number
+                        .split(',')
+                        .filter_map(|number| number.trim().parse().ok())
+                        .collect();
+                    if !passes.is_empty() {
+                        self.ending_start = Some(passes);
+                    }
+                }
+                StartStopDiscontinue::Stop | StartStopDiscontinue::Discontinue => {
+                    self.ending_end = true
+                }
+            }
+        }
+
+        self.segno |= barline.content.segno.is_some() || barline.attributes.segno.is_some();
+        self.coda |= barline.content.coda.is_some() || barline.attributes.coda.is_some();
+    }
+
+    fn add_direction(&mut self, direction: &Direction) {
+        // A coda sign with tocoda playback is the "To Coda", not the coda itself
+        let is_to_coda = direction
+            .content
+            .sound
+            .as_ref()
+            .is_some_and(|sound| sound.attributes.tocoda.is_some());
+
+        for direction_type in &direction.content.direction_type {
+            match &direction_type.content {
+                DirectionTypeContents::Segno(_) => self.segno = true,
+                DirectionTypeContents::Coda(_) => self.coda |= !is_to_coda,
+                DirectionTypeContents::Words(words) => {
+                    for words in words {
+                        self.add_words(&words.content);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(sound) = &direction.content.sound {
+            self.add_sound(sound);
+        }
+    }
+
+    // Fallback for scores that write the jumps without playback attributes
+    fn add_words(&mut self, text: &str) {
+        let text = text.to_lowercase();
+        let text = text.trim();
+
+        if text.starts_with("d.c.") || text.starts_with("da capo") {
+            self.jump = Some(Jump::DaCapo);
+        } else if text.starts_with("d.s.") || text.starts_with("dal segno") {
+            self.jump = Some(Jump::DalSegno);
+        } else if text == "to coda" {
+            self.to_coda = true;
+        } else if text == "fine" {
+            self.fine = true;
+        }
+    }
+
+    fn add_sound(&mut self, sound: &Sound) {
+        let attributes = &sound.attributes;
+        self.segno |= attributes.segno.is_some();
+        self.coda |= attributes.coda.is_some();
+        self.to_coda |= attributes.tocoda.is_some();
+        self.fine |= attributes.fine.is_some();
+
+        if attributes.dacapo == Some(YesNo::Yes) {
+            self.jump = Some(Jump::DaCapo);
+        } else if attributes.dalsegno.is_some() {
+            self.jump = Some(Jump::DalSegno);
+        }
+    }
+}
+
+// Indices of the written measures in the order they are played. After a
+// D.C. or D.S., repeats are not taken again and only the last ending is played.
+pub fn performance_order(measures: &[MeasureNavigation]) -> Vec<usize> {
+    let mut order = Vec::new();
+    let mut taken_jumps = vec![false; measures.len()];
+    let mut index = 0;
+    let mut repeat_start = 0;
+    let mut pass = 1;
+    let mut returning = false;
+    let mut after_jump = false;
+
+    while index < measures.len() && order.len() < measures.len() * MAX_PLAYS_PER_MEASURE {
+        let measure = &measures[index];
+
+        if measure.forward_repeat && !returning {
+            repeat_start = index;
+            pass = 1;
+        }
+        returning = false;
+
+        if let Some(passes) = &measure.ending_start {
+            let ending_end = find_ending_end(measures, index);
+            let leads_back = measures[ending_end].backward_repeat.is_some();
+            let plays_ending = if after_jump {
+                !leads_back
+            } else {
+                passes.contains(&pass)
+            };
+            if !plays_ending {
+                index = ending_end + 1;
+                continue;
+            }
+        }
+
+        order.push(index);
+
+        if after_jump && measure.fine {
+            break;
+        }
+        if after_jump
+            && measure.to_coda
+            && let Some(coda) = (index + 1..measures.len()).find(|&next| measures[next].coda)
+        {
+            index = coda;
+            continue;
+        }
+
+        if let Some(times) = measure.backward_repeat
+            && !after_jump
+        {
+            if pass < times {
+                pass += 1;
+                returning = true;
+                index = repeat_start;
+                continue;
+            }
+            pass = 1;
+            repeat_start = index + 1;
+        } else if measure.ending_end {
+            // The last ending leaves the repeated section
+            pass = 1;
+            repeat_start = index + 1;
+        }
+
+        if let Some(jump) = measure.jump
+            && !taken_jumps[index]
+        {
+            taken_jumps[index] = true;
+            after_jump = true;
+            index = match jump {
+                Jump::DaCapo => 0,
+                Jump::DalSegno => measures.iter().position(|m| m.segno).unwrap_or(0),
+            };
+            repeat_start = index;
+            pass = 1;
+            returning = true;
+            continue;
+        }
+
+        index += 1;
+    }
+
+    order
+}
+
+fn find_ending_end(measures: &[MeasureNavigation], ending_start: usize) -> usize {
+    (ending_start..measures.len())
+        .find(|&index| {
+            measures[index].ending_end
+                || (index > ending_start && measures[index].ending_start.is_some())
+        })
+        .map_or(ending_start, |index| {
+            if measures[index].ending_end {
+                index
+            } else {
+                index - 1
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    // 1 |: 2 3 :| 4
+    #[case(
+        vec![plain(), forward(), backward(2), plain()],
+        vec![0, 1, 2, 1, 2, 3]
+    )]
+    // No forward repeat: back to the start, played three times
+    #[case(vec![plain(), backward(3), plain()], vec![0, 1, 0, 1, 0, 1, 2])]
+    // 1 |: 2 |1. 3 :|2. 4 | 5
+    #[case(
+        vec![plain(), forward(), ending(&[1], true, Some(2)), ending(&[2], true, None), plain()],
+        vec![0, 1, 2, 1, 3, 4]
+    )]
+    // Two repeated sections in a row, the second without a forward repeat
+    #[case(
+        vec![backward(2), plain(), backward(2)],
+        vec![0, 0, 1, 2, 1, 2]
+    )]
+    fn test_performance_order_repeats_and_endings(
+        #[case] measures: Vec<MeasureNavigation>,
+        #[case] expected: Vec<usize>,
+    ) {
+        // Act
+        let actual = performance_order(&measures);
+
+        // Assert
+        assert_eq!(actual, expected);
+    }
+
+    fn plain() -> MeasureNavigation {
+        MeasureNavigation::default()
+    }
+
+    fn forward() -> MeasureNavigation {
+        MeasureNavigation {
+            forward_repeat: true,
+            ..MeasureNavigation::default()
+        }
+    }
+
+    fn backward(times: u32) -> MeasureNavigation {
+        MeasureNavigation {
+            backward_repeat: Some(times),
+            ..MeasureNavigation::default()
+        }
+    }
+
+    fn ending(passes: &[u32], ends: bool, backward_repeat: Option<u32>) -> MeasureNavigation {
+        MeasureNavigation {
+            ending_start: Some(passes.to_vec()),
+            ending_end: ends,
+            backward_repeat,
+            ..MeasureNavigation::default()
+        }
+    }
+
+    #[test]
+    fn test_performance_order_da_capo_al_fine_skips_repeats() {
+        // Arrange
+        // |: 1 Fine :| 2 D.C. al Fine
+        let measures = vec![
+            MeasureNavigation {
+                fine: true,
+                ..backward(2)
+            },
+            MeasureNavigation {
+                jump: Some(Jump::DaCapo),
+                ..plain()
+            },
+        ];
+
+        // Act
+        let actual = performance_order(&measures);
+
+        // Assert
+        assert_eq!(actual, vec![0, 0, 1, 0]);
+    }
+
+    #[test]
+    fn test_performance_order_dal_segno_al_coda() {
+        // Arrange
+        // 1 segno 2 to-coda |1. 3 :|2. 4 | 5 D.S. al Coda | coda 6
+        let measures = vec![
+            plain(),
+            MeasureNavigation {
+                segno: true,
+                forward_repeat: true,
+                to_coda: true,
+                ..plain()
+            },
+            ending(&[1], true, Some(2)),
+            ending(&[2], true, None),
+            MeasureNavigation {
+                jump: Some(Jump::DalSegno),
+                ..plain()
+            },
+            MeasureNavigation {
+                coda: true,
+                ..plain()
+            },
+        ];
+
+        // Act
+        let actual = performance_order(&measures);
+
+        // Assert
+        assert_eq!(actual, vec![0, 1, 2, 1, 3, 4, 1, 5]);
+    }
+
+    #[test]
+    fn test_performance_order_ignores_to_coda_before_jump() {
+        // Arrange
+        let measures = vec![
+            MeasureNavigation {
+                to_coda: true,
+                ..plain()
+            },
+            plain(),
+            MeasureNavigation {
+                coda: true,
+                ..plain()
+            },
+        ];
+
+        // Act
+        let actual = performance_order(&measures);
+
+        // Assert
+        assert_eq!(actual, vec![0, 1, 2]);
+    }
+}
@@ -0,0 +1,215 @@
+// Tempos in the middle of this range are neither fast nor slow, so modifiers
+// like "molto" push a term away from it and "ma non troppo" towards it
+const MODERATE_BEATS_PER_MINUTE: f64 = 108.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TempoTerm {
+    pub name: String, // lowercase, "allegro" or "sehr langsam"
+    pub min_beats_per_minute: f64,
+    pub max_beats_per_minute: f64,
+}
+
+impl TempoTerm {
+    pub fn new(name: &str, min_beats_per_minute: f64, max_beats_per_minute: f64) -> Self {
+        Self {
+            name: name.to_string(),
+            min_beats_per_minute,
+            max_beats_per_minute,
+        }
+    }
+
+    // 0.0 is the end of the range closest to a moderate tempo, 1.0 the other end
+    fn beats_per_minute_at(&self, position: f64) -> f64 {
+        let (moderate_end, extreme_end) = if self.is_slow() {
+            (self.max_beats_per_minute, self.min_beats_per_minute)
+        } else {
+            (self.min_beats_per_minute, self.max_beats_per_minute)
+        };
+        moderate_end + (extreme_end - moderate_end) * position
+    }
+
+    fn is_slow(&self) -> bool {
+        (self.min_beats_per_minute + self.max_beats_per_minute) / 2.0 < MODERATE_BEATS_PER_MINUTE
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TempoModifier {
+    pub name: String,  // lowercase, "molto" or "ma non troppo"
+    pub position: f64, // within the range of the term, see TempoTerm::beats_per_minute_at
+}
+
+impl TempoModifier {
+    pub fn new(name: &str, position: f64) -> Self {
+        Self {
+            name: name.to_string(),
+            position,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TempoTermTable {
+    pub terms: Vec<TempoTerm>,
+    pub modifiers: Vec<TempoModifier>,
+}
+
+impl Default for TempoTermTable {
+    fn default() -> Self {
+        let terms = [
+            // Italian
+            ("larghissimo", 20.0, 24.0),
+            ("grave", 25.0, 45.0),
+            ("largo", 40.0, 60.0),
+            ("lento", 45.0, 60.0),
+            ("larghetto", 60.0, 66.0),
+            ("adagio", 66.0, 76.0),
+            ("adagietto", 70.0, 80.0),
+            ("andante", 76.0, 108.0),
+            ("andantino", 80.0, 108.0),
+            ("moderato", 108.0, 120.0),
+            ("allegretto", 112.0, 120.0),
+            ("allegro", 120.0, 156.0),
+            ("animato", 120.0, 156.0),
+            ("vivace", 156.0, 176.0),
+            ("vivacissimo", 172.0, 176.0),
+            ("allegrissimo", 172.0, 176.0),
+            ("presto", 168.0, 200.0),
+            ("prestissimo", 200.0, 240.0),
+            // German
+            ("langsam", 45.0, 60.0),
+            ("gehend", 76.0, 108.0),
+            ("mäßig", 108.0, 120.0),
+            ("mässig", 108.0, 120.0),
+            ("massig", 108.0, 120.0),
+            ("bewegt", 108.0, 132.0),
+            ("lebhaft", 120.0, 156.0),
+            ("schnell", 156.0, 176.0),
+            ("rasch", 156.0, 176.0),
+            // French
+            ("lent", 45.0, 60.0),
+            ("modéré", 108.0, 120.0),
+            ("modere", 108.0, 120.0),
+            ("animé", 120.0, 156.0),
+            ("anime", 120.0, 156.0),
+            ("vif", 156.0, 176.0),
+            ("vite", 168.0, 200.0),
+        ];
+        let modifiers = [
+            ("molto", 1.0),
+            ("assai", 1.0),
+            ("sehr", 1.0),
+            ("très", 1.0),
+            ("tres", 1.0),
+            ("ma non troppo", 0.0),
+            ("non troppo", 0.0),
+            ("ma non tanto", 0.0),
+            ("nicht zu", 0.0),
+            ("pas trop", 0.0),
+        ];
+
+        Self {
+            terms: terms
+                .iter()
+                .map(|&(name, min, max)| TempoTerm::new(name, min, max))
+                .collect(),
+            modifiers: modifiers
+                .iter()
+                .map(|&(name, position)| TempoModifier::new(name, position))
+                .collect(),
+        }
+    }
+}
+
+impl TempoTermTable {
+    // The first term written wins, "Andante con moto" is an andante
+    pub fn beats_per_minute(&self, text: &str) -> Option<f64> {
+        let text = normalize(text);
+        let term = self
+            .terms
+            .iter()
+            .filter_map(|term| Some((find_words(&text, &term.name)?, term)))
+            .min_by_key(|&(index, term)| (index, std::cmp::Reverse(term.name.len())))?
+            .1;
+        let position = self
+            .modifiers
+            .iter()
+            .find(|modifier| find_words(&text, &modifier.name).is_some())
+            .map_or(0.5, |modifier| modifier.position);
+
+        Some(term.beats_per_minute_at(position))
+    }
+}
+
+// Lowercase words separated by single spaces, with a space on either side
+fn normalize(text: &str) -> String {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    format!(" {} ", words.join(" "))
+}
+
+fn find_words(normalized_text: &str, words: &str) -> Option<usize> {
+    normalized_text.find(&format!(" {words} "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_float_eq::assert_float_absolute_eq;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("Allegro", 138.0)]
+    #[case("Allegro molto", 156.0)]
+    #[case("Allegro ma non troppo", 120.0)]
+    #[case("Molto adagio", 66.0)] // slow terms get slower
+    #[case("Adagio ma non troppo", 76.0)]
+    #[case("Andante con moto", 92.0)]
+    #[case("Allegretto", 116.0)]
+    #[case("Sehr langsam", 45.0)]
+    #[case("Mäßig bewegt", 114.0)]
+    #[case("Très vif", 176.0)]
+    fn test_tempo_term_table_beats_per_minute(#[case] text: &str, #[case] expected: f64) {
+        // Arrange
+        let sut = TempoTermTable::default();
+
+        // Act
+        let actual = sut.beats_per_minute(text).unwrap();
+
+        // Assert
+        assert_float_absolute_eq!(actual, expected);
+    }
+
+    #[rstest]
+    #[case("dolce")]
+    #[case("cresc.")]
+    #[case("allegramente")] // words only match as a whole
+    fn test_tempo_term_table_ignores_other_words(#[case] text: &str) {
+        // Arrange
+        let sut = TempoTermTable::default();
+
+        // Act
+        let actual = sut.beats_per_minute(text);
+
+        // Assert
+        assert_eq!(actual, None);
+    }
+
+    #[test]
+    fn test_tempo_term_table_uses_custom_terms() {
+        // Arrange
+        let sut = TempoTermTable {
+            terms: vec![TempoTerm::new("swing", 100.0, 140.0)],
+            modifiers: Vec::new(),
+        };
+
+        // Act
+        let actual = sut.beats_per_minute("Medium Swing");
+
+        // Assert
+        assert_eq!(actual, Some(120.0));
+    }
+}
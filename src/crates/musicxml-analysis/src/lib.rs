@@ -1,4 +1,5 @@
 pub mod analysis;
+pub mod error;
 pub mod extraction;
 pub mod model;
 pub mod statistics;
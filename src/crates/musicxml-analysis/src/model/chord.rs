@@ -0,0 +1,166 @@
+use std::collections::BTreeSet;
+
+use super::ChromaticNoteName;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TriadQuality {
+    Major,
+    Minor,
+    Diminished,
+    Augmented,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SeventhQuality {
+    Dominant,
+    Major,
+    Minor,
+    HalfDiminished,
+    Diminished,
+    MinorMajor,
+    AugmentedMajor,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ChordKind {
+    Triad(TriadQuality),
+    Seventh(SeventhQuality),
+    Extended(SeventhQuality), // ninths, elevenths and thirteenths over this seventh
+}
+
+// Semitones above the root, the root itself included
+const TRIADS: [(TriadQuality, &[i32]); 4] = [
+    (TriadQuality::Major, &[0, 4, 7]),
+    (TriadQuality::Minor, &[0, 3, 7]),
+    (TriadQuality::Diminished, &[0, 3, 6]),
+    (TriadQuality::Augmented, &[0, 4, 8]),
+];
+
+const SEVENTHS: [(SeventhQuality, &[i32]); 7] = [
+    (SeventhQuality::Dominant, &[0, 4, 7, 10]),
+    (SeventhQuality::Major, &[0, 4, 7, 11]),
+    (SeventhQuality::Minor, &[0, 3, 7, 10]),
+    (SeventhQuality::HalfDiminished, &[0, 3, 6, 10]),
+    (SeventhQuality::Diminished, &[0, 3, 6, 9]),
+    (SeventhQuality::MinorMajor, &[0, 3, 7, 11]),
+    (SeventhQuality::AugmentedMajor, &[0, 4, 8, 11]),
+];
+
+// Ninths, elevenths and thirteenths in semitones above the root, each in any
+// alteration: flat, natural and sharp ninth, natural and sharp eleventh, flat
+// and natural thirteenth
+const EXTENSIONS: [i32; 7] = [1, 2, 3, 5, 6, 8, 9];
+
+// What a vertical sonority of three or more pitch classes is heard as
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Chord {
+    Tertian {
+        root: ChromaticNoteName,
+        kind: ChordKind,
+    },
+    Unclassified(BTreeSet<ChromaticNoteName>), // clusters, quartal and other non-tertian sets
+}
+
+impl Chord {
+    // None below three pitch classes. Of several possible roots, as in augmented
+    // triads and diminished sevenths, the bass wins, then the lowest pitch class.
+    pub fn from_pitch_classes(
+        pitch_classes: &BTreeSet<ChromaticNoteName>,
+        bass: ChromaticNoteName,
+    ) -> Option<Self> {
+        if pitch_classes.len() < 3 {
+            return None;
+        }
+
+        let candidate_roots = std::iter::once(bass).chain(pitch_classes.iter().copied());
+        for root in candidate_roots {
+            let intervals: BTreeSet<i32> = pitch_classes
+                .iter()
+                .map(|pitch_class| (*pitch_class as i32 - root as i32).rem_euclid(12))
+                .collect();
+            if let Some(kind) = classify(&intervals) {
+                return Some(Chord::Tertian { root, kind });
+            }
+        }
+        Some(Chord::Unclassified(pitch_classes.clone()))
+    }
+
+    pub fn is_tertian(&self) -> bool {
+        matches!(self, Chord::Tertian { .. })
+    }
+}
+
+fn classify(intervals: &BTreeSet<i32>) -> Option<ChordKind> {
+    let matches = |template: &[i32]| template.iter().all(|i| intervals.contains(i));
+    match intervals.len() {
+        3 => TRIADS
+            .iter()
+            .find(|(_, template)| matches(template))
+            .map(|(quality, _)| ChordKind::Triad(*quality)),
+        4 => SEVENTHS
+            .iter()
+            .find(|(_, template)| matches(template))
+            .map(|(quality, _)| ChordKind::Seventh(*quality)),
+        5..=7 => SEVENTHS
+            .iter()
+            .find(|(_, template)| matches(template) && are_extensions(template, intervals))
+            .map(|(quality, _)| ChordKind::Extended(*quality)),
+        _ => None,
+    }
+}
+
+// The tones beyond the seventh must be ninths, elevenths or thirteenths, never
+// another seventh, and no two of them a semitone apart, as a flat and a natural
+// ninth are in a cluster
+fn are_extensions(template: &[i32], intervals: &BTreeSet<i32>) -> bool {
+    let extensions: Vec<i32> = intervals
+        .iter()
+        .copied()
+        .filter(|interval| !template.contains(interval))
+        .collect();
+    extensions
+        .iter()
+        .all(|interval| EXTENSIONS.contains(interval))
+        && extensions.windows(2).all(|pair| pair[1] - pair[0] > 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    use ChromaticNoteName::*;
+
+    #[rstest]
+    #[case(&[C, E, G], C, Some(Chord::Tertian { root: C, kind: ChordKind::Triad(TriadQuality::Major) }))]
+    // First inversion keeps the root
+    #[case(&[C, E, A], C, Some(Chord::Tertian { root: A, kind: ChordKind::Triad(TriadQuality::Minor) }))]
+    #[case(&[G, B, D, F], B, Some(Chord::Tertian { root: G, kind: ChordKind::Seventh(SeventhQuality::Dominant) }))]
+    // Symmetric chords take the bass as root
+    #[case(&[C, E, GSharp], E, Some(Chord::Tertian { root: E, kind: ChordKind::Triad(TriadQuality::Augmented) }))]
+    #[case(&[B, D, F, GSharp], D, Some(Chord::Tertian { root: D, kind: ChordKind::Seventh(SeventhQuality::Diminished) }))]
+    #[case(&[C, E, G, B, D], C, Some(Chord::Tertian { root: C, kind: ChordKind::Extended(SeventhQuality::Major) }))]
+    #[case(&[C, E, G, ASharp, CSharp, DSharp], C, Some(Chord::Tertian { root: C, kind: ChordKind::Extended(SeventhQuality::Dominant) }))]
+    #[case(&[C, CSharp, D], C, Some(Chord::Unclassified(BTreeSet::from([C, CSharp, D]))))]
+    // A major seventh over a minor seventh is no extension
+    #[case(&[C, E, G, ASharp, B], C, Some(Chord::Unclassified(BTreeSet::from([C, E, G, ASharp, B]))))]
+    // Neither are clusters that happen to contain a seventh chord
+    #[case(&[C, CSharp, D, E, G, ASharp], C, Some(Chord::Unclassified(BTreeSet::from([C, CSharp, D, E, G, ASharp]))))]
+    // Quartal and suspended sonorities are not tertian
+    #[case(&[C, F, G], C, Some(Chord::Unclassified(BTreeSet::from([C, F, G]))))]
+    #[case(&[C, G], C, None)]
+    fn test_chord_from_pitch_classes(
+        #[case] pitch_classes: &[ChromaticNoteName],
+        #[case] bass: ChromaticNoteName,
+        #[case] expected: Option<Chord>,
+    ) {
+        // Arrange
+        let pitch_classes: BTreeSet<ChromaticNoteName> = pitch_classes.iter().copied().collect();
+
+        // Act
+        let actual = Chord::from_pitch_classes(&pitch_classes, bass);
+
+        // Assert
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,42 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClefSign {
+    G,
+    F,
+    C,
+    Percussion,
+    Tab,
+    Jianpu,
+    None, // the staff has no clef
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Clef {
+    pub sign: ClefSign,
+    pub line: Option<i32>,  // staff line of the sign, counted from the bottom
+    pub octave_change: i32, // -1 for the tenor G clef with the 8 below
+}
+
+impl Clef {
+    pub fn new(sign: ClefSign, line: Option<i32>, octave_change: i32) -> Self {
+        Self {
+            sign,
+            line,
+            octave_change,
+        }
+    }
+
+    pub fn treble() -> Self {
+        Self::new(ClefSign::G, Some(2), 0)
+    }
+
+    pub fn bass() -> Self {
+        Self::new(ClefSign::F, Some(4), 0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClefChange {
+    pub staff: u32,
+    pub position_quarters: f64, // relative to the start of the measure
+    pub clef: Clef,
+}
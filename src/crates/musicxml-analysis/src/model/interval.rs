@@ -0,0 +1,150 @@
+use std::fmt;
+
+// Semitones above the lower note of the major or perfect interval of each step count
+const MAJOR_OR_PERFECT_SEMITONES: [i32; 7] = [0, 2, 4, 5, 7, 9, 11];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IntervalQuality {
+    Diminished(u32), // 2 for doubly diminished
+    Minor,
+    Perfect,
+    Major,
+    Augmented(u32), // 2 for doubly augmented
+}
+
+// Distance in pitch alone, C to E and C to F flat are both 400 cents
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ChromaticInterval {
+    pub cents: i32, // negative downward
+}
+
+impl ChromaticInterval {
+    pub fn semitones(&self) -> f64 {
+        self.cents as f64 / 100.0
+    }
+}
+
+// Spelled distance: C to E is a major third, C to F flat a diminished fourth
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Interval {
+    pub steps: i32, // 0 for a unison, 2 for a third, negative downward
+    pub cents: i32, // negative downward
+}
+
+impl Interval {
+    pub fn new(steps: i32, cents: i32) -> Self {
+        Self { steps, cents }
+    }
+
+    pub fn from_semitones(steps: i32, semitones: i32) -> Self {
+        Self::new(steps, semitones * 100)
+    }
+
+    // 1 for a unison, 3 for a third, 8 for an octave, whatever the direction
+    pub fn number(&self) -> u32 {
+        self.steps.unsigned_abs() + 1
+    }
+
+    pub fn is_descending(&self) -> bool {
+        self.steps < 0 || (self.steps == 0 && self.cents < 0)
+    }
+
+    pub fn chromatic(&self) -> ChromaticInterval {
+        ChromaticInterval { cents: self.cents }
+    }
+
+    // None for microtonal intervals, which have no traditional quality
+    pub fn quality(&self) -> Option<IntervalQuality> {
+        let (steps, cents) = if self.steps < 0 {
+            (-self.steps, -self.cents)
+        } else {
+            (self.steps, self.cents)
+        };
+        if cents % 100 != 0 {
+            return None;
+        }
+
+        let reference =
+            MAJOR_OR_PERFECT_SEMITONES[steps.rem_euclid(7) as usize] + 12 * steps.div_euclid(7);
+        let deviation = cents / 100 - reference;
+        let is_perfect_class = matches!(steps.rem_euclid(7), 0 | 3 | 4);
+
+        Some(match (is_perfect_class, deviation) {
+            (true, 0) => IntervalQuality::Perfect,
+            (false, 0) => IntervalQuality::Major,
+            (false, -1) => IntervalQuality::Minor,
+            (_, deviation) if deviation > 0 => IntervalQuality::Augmented(deviation as u32),
+            (true, deviation) => IntervalQuality::Diminished(deviation.unsigned_abs()),
+            (false, deviation) => IntervalQuality::Diminished(deviation.unsigned_abs() - 1),
+        })
+    }
+}
+
+// "m3", "P5", "A2", "dd7"; microtonal intervals show their size in cents
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let direction = if self.is_descending() { "-" } else { "" };
+        let quality = match self.quality() {
+            Some(IntervalQuality::Perfect) => "P".to_string(),
+            Some(IntervalQuality::Major) => "M".to_string(),
+            Some(IntervalQuality::Minor) => "m".to_string(),
+            Some(IntervalQuality::Augmented(count)) => "A".repeat(count as usize),
+            Some(IntervalQuality::Diminished(count)) => "d".repeat(count as usize),
+            None => return write!(f, "{direction}{}c{}", self.cents.abs(), self.number()),
+        };
+        write!(f, "{direction}{quality}{}", self.number())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Interval::from_semitones(0, 0), Some(IntervalQuality::Perfect), "P1")]
+    #[case(Interval::from_semitones(2, 3), Some(IntervalQuality::Minor), "m3")]
+    #[case(
+        Interval::from_semitones(1, 3),
+        Some(IntervalQuality::Augmented(1)),
+        "A2"
+    )]
+    #[case(
+        Interval::from_semitones(3, 4),
+        Some(IntervalQuality::Diminished(1)),
+        "d4"
+    )]
+    #[case(
+        Interval::from_semitones(3, 6),
+        Some(IntervalQuality::Augmented(1)),
+        "A4"
+    )]
+    #[case(
+        Interval::from_semitones(6, 9),
+        Some(IntervalQuality::Diminished(1)),
+        "d7"
+    )]
+    #[case(
+        Interval::from_semitones(6, 8),
+        Some(IntervalQuality::Diminished(2)),
+        "dd7"
+    )]
+    #[case(Interval::from_semitones(7, 12), Some(IntervalQuality::Perfect), "P8")]
+    #[case(Interval::from_semitones(9, 16), Some(IntervalQuality::Major), "M10")]
+    #[case(Interval::from_semitones(-4, -7), Some(IntervalQuality::Perfect), "-P5")]
+    #[case(Interval::from_semitones(0, -1), Some(IntervalQuality::Diminished(1)), "-d1")]
+    #[case(Interval::new(1, 150), None, "150c2")]
+    fn test_interval_quality_and_display(
+        #[case] interval: Interval,
+        #[case] expected_quality: Option<IntervalQuality>,
+        #[case] expected_display: &str,
+    ) {
+        // Act
+        let quality = interval.quality();
+        let display = interval.to_string();
+
+        // Assert
+        assert_eq!(quality, expected_quality);
+        assert_eq!(display, expected_display);
+    }
+}
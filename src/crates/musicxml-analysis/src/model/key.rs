@@ -0,0 +1,133 @@
+use std::fmt;
+
+use super::{ChromaticNoteName, KeyMode};
+
+// Steps along the line of fifths from F, so index 1 is C and 7 is F sharp
+const LETTERS_BY_FIFTHS: [char; 7] = ['F', 'C', 'G', 'D', 'A', 'E', 'B'];
+
+// How far the tonic lies up the circle of fifths from the tonic of the major key
+// with the same key signature, 3 for A minor in relation to C major
+fn fifths_above_relative_major(mode: KeyMode) -> i32 {
+    match mode {
+        KeyMode::Major | KeyMode::Ionian | KeyMode::None => 0,
+        KeyMode::Lydian => -1,
+        KeyMode::Mixolydian => 1,
+        KeyMode::Dorian => 2,
+        KeyMode::Minor | KeyMode::Aeolian => 3,
+        KeyMode::Phrygian => 4,
+        KeyMode::Locrian => 5,
+    }
+}
+
+// A tonal center, as opposed to the key signature it is written with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Key {
+    pub tonic: ChromaticNoteName,
+    pub mode: KeyMode,
+}
+
+impl Key {
+    pub fn new(tonic: ChromaticNoteName, mode: KeyMode) -> Self {
+        Self { tonic, mode }
+    }
+
+    // Sharps when positive, flats when negative, always between -5 and 6, so
+    // F sharp major is preferred over G flat major
+    pub fn fifths(&self) -> i32 {
+        let tonic_fifths = self.tonic as i32 * 7;
+        (tonic_fifths - fifths_above_relative_major(self.mode) + 5).rem_euclid(12) - 5
+    }
+
+    // Steps around the circle of fifths between the two key signatures, the
+    // short way round, so relative keys are 0 apart
+    pub fn fifths_distance_to(&self, other: &Key) -> u32 {
+        let distance = (self.fifths() - other.fifths()).rem_euclid(12);
+        distance.min(12 - distance) as u32
+    }
+}
+
+impl fmt::Display for Key {
+    // The tonic is spelled the way the key signature implies, F# major but Bb minor
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let tonic_fifths = self.fifths() + fifths_above_relative_major(self.mode) + 1;
+        write!(
+            f,
+            "{}",
+            LETTERS_BY_FIFTHS[tonic_fifths.rem_euclid(7) as usize]
+        )?;
+        let alteration = tonic_fifths.div_euclid(7);
+        let accidental = if alteration < 0 { "b" } else { "#" };
+        write!(
+            f,
+            "{}",
+            accidental.repeat(alteration.unsigned_abs() as usize)
+        )?;
+        match self.mode {
+            KeyMode::None => Ok(()),
+            mode => write!(f, " {}", format!("{mode:?}").to_lowercase()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(ChromaticNoteName::C, KeyMode::Major, 0, "C major")]
+    #[case(ChromaticNoteName::A, KeyMode::Minor, 0, "A minor")]
+    #[case(ChromaticNoteName::FSharp, KeyMode::Major, 6, "F# major")]
+    #[case(ChromaticNoteName::CSharp, KeyMode::Major, -5, "Db major")]
+    #[case(ChromaticNoteName::ASharp, KeyMode::Minor, -5, "Bb minor")]
+    #[case(ChromaticNoteName::DSharp, KeyMode::Minor, 6, "D# minor")]
+    #[case(ChromaticNoteName::D, KeyMode::Dorian, 0, "D dorian")]
+    #[case(ChromaticNoteName::G, KeyMode::None, 1, "G")]
+    fn test_key_fifths_and_display(
+        #[case] tonic: ChromaticNoteName,
+        #[case] mode: KeyMode,
+        #[case] expected_fifths: i32,
+        #[case] expected_display: &str,
+    ) {
+        // Arrange
+        let sut = Key::new(tonic, mode);
+
+        // Act
+        let actual_fifths = sut.fifths();
+        let actual_display = sut.to_string();
+
+        // Assert
+        assert_eq!(actual_fifths, expected_fifths);
+        assert_eq!(actual_display, expected_display);
+    }
+
+    #[rstest]
+    #[case(
+        Key::new(ChromaticNoteName::C, KeyMode::Major),
+        Key::new(ChromaticNoteName::G, KeyMode::Major),
+        1
+    )]
+    #[case(
+        Key::new(ChromaticNoteName::C, KeyMode::Major),
+        Key::new(ChromaticNoteName::A, KeyMode::Minor),
+        0
+    )]
+    #[case(
+        Key::new(ChromaticNoteName::C, KeyMode::Major),
+        Key::new(ChromaticNoteName::FSharp, KeyMode::Major),
+        6
+    )]
+    #[case(
+        Key::new(ChromaticNoteName::B, KeyMode::Major),
+        Key::new(ChromaticNoteName::CSharp, KeyMode::Major),
+        2
+    )]
+    fn test_key_fifths_distance_to(#[case] from: Key, #[case] to: Key, #[case] expected: u32) {
+        // Act
+        let actual = from.fifths_distance_to(&to);
+
+        // Assert
+        assert_eq!(actual, expected);
+    }
+}
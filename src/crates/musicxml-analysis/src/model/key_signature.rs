@@ -0,0 +1,124 @@
+use super::{Accidental, NoteName};
+
+const SHARP_ORDER: [NoteName; 7] = [
+    NoteName::F,
+    NoteName::C,
+    NoteName::G,
+    NoteName::D,
+    NoteName::A,
+    NoteName::E,
+    NoteName::B,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyMode {
+    Major,
+    Minor,
+    Ionian,
+    Dorian,
+    Phrygian,
+    Lydian,
+    Mixolydian,
+    Aeolian,
+    Locrian,
+    None, // the key signature does not imply a mode
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum KeySignature {
+    Traditional {
+        fifths: i8, // sharps when positive, flats when negative
+        mode: Option<KeyMode>,
+    },
+    NonTraditional(Vec<(NoteName, Accidental)>), // altered steps in written order
+}
+
+impl KeySignature {
+    pub fn traditional(fifths: i8, mode: Option<KeyMode>) -> Self {
+        KeySignature::Traditional { fifths, mode }
+    }
+
+    // What the key signature does to a step without an accidental of its own
+    pub fn accidental_for(&self, note_name: NoteName) -> Accidental {
+        match self {
+            KeySignature::Traditional { fifths, .. } => {
+                let count = fifths.unsigned_abs() as usize;
+                if *fifths > 0 && SHARP_ORDER.iter().take(count).any(|n| *n == note_name) {
+                    Accidental::Sharp
+                } else if *fifths < 0
+                    && SHARP_ORDER
+                        .iter()
+                        .rev()
+                        .take(count)
+                        .any(|n| *n == note_name)
+                {
+                    Accidental::Flat
+                } else {
+                    Accidental::Natural
+                }
+            }
+            KeySignature::NonTraditional(alterations) => alterations
+                .iter()
+                .find(|(altered, _)| *altered == note_name)
+                .map_or(Accidental::Natural, |(_, accidental)| *accidental),
+        }
+    }
+}
+
+impl Default for KeySignature {
+    fn default() -> Self {
+        KeySignature::traditional(0, None)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyChange {
+    pub staff: Option<u32>,     // None applies to every staff of the part
+    pub position_quarters: f64, // relative to the start of the measure
+    pub key: KeySignature,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    // D major: F and C sharp
+    #[case(
+        KeySignature::traditional(2, Some(KeyMode::Major)),
+        NoteName::C,
+        Accidental::Sharp
+    )]
+    #[case(
+        KeySignature::traditional(2, Some(KeyMode::Major)),
+        NoteName::G,
+        Accidental::Natural
+    )]
+    // E flat major: B, E and A flat
+    #[case(KeySignature::traditional(-3, None), NoteName::A, Accidental::Flat)]
+    #[case(KeySignature::traditional(-3, None), NoteName::D, Accidental::Natural)]
+    // C sharp major: every step sharp
+    #[case(KeySignature::traditional(7, None), NoteName::B, Accidental::Sharp)]
+    #[case(
+        KeySignature::NonTraditional(vec![(NoteName::B, Accidental::Flat), (NoteName::F, Accidental::Sharp)]),
+        NoteName::F,
+        Accidental::Sharp
+    )]
+    #[case(
+        KeySignature::NonTraditional(vec![(NoteName::B, Accidental::QuarterFlat)]),
+        NoteName::E,
+        Accidental::Natural
+    )]
+    fn test_accidental_for(
+        #[case] key: KeySignature,
+        #[case] note_name: NoteName,
+        #[case] expected: Accidental,
+    ) {
+        // Act
+        let actual = key.accidental_for(note_name);
+
+        // Assert
+        assert_eq!(actual, expected);
+    }
+}
@@ -1,22 +1,28 @@
 use std::collections::HashSet;
 
-use crate::model::Pitch;
+use crate::model::{PercussionInstrument, Pitch};
 
-use super::TimeSignature;
+use super::{Tempo, TimeSignature, TupletUsage};
 
 #[derive(Debug, PartialEq)]
 pub struct MeasureData {
+    pub number: String, // as written, not the position in the score
     pub note_count: u32,
-    pub tempo_bpm: f64,
+    pub onset_count: u32,      // distinct note starts, a chord counts once
+    pub chord_sizes: Vec<u32>, // notes in each chord starting in the measure
+    pub grace_note_count: u32, // not part of note_count
+    pub tuplets: TupletUsage,
+    pub tempo: Tempo, // averaged over the measure when the tempo changes inside it
     pub time_signature: TimeSignature,
-    pub pitches: HashSet<Pitch>,
+    pub length_quarters: f64, // notated content, shorter than the time signature in pickups
+    pub pitches: HashSet<Pitch>, // of pitched notes only, as written
+    pub sounding_pitches: HashSet<Pitch>, // in concert pitch, with octave doublings
+    pub percussion_instruments: HashSet<PercussionInstrument>, // of unpitched notes
 }
 
 impl MeasureData {
     pub fn get_measure_duration_seconds(&self) -> f64 {
-        let seconds_per_beat = 60.0 / self.tempo_bpm;
-        let beats_per_measure = self.time_signature.beats_per_measure() as f64;
-        seconds_per_beat * beats_per_measure
+        self.length_quarters * self.tempo.seconds_per_quarter_note()
     }
 }
 
@@ -30,10 +36,18 @@ mod tests {
     fn test_get_measure_duration_seconds_standard_4_4_at_120_bpm() {
         // Arrange
         let measure_data = MeasureData {
+            number: "1".to_string(),
             note_count: 4,
-            tempo_bpm: 120.0,
+            onset_count: 4,
+            chord_sizes: Vec::new(),
+            grace_note_count: 0,
+            tuplets: TupletUsage::default(),
+            tempo: Tempo::quarter_notes(120.0),
             time_signature: TimeSignature::new(4, 4),
+            length_quarters: 4.0,
             pitches: HashSet::new(),
+            sounding_pitches: HashSet::new(),
+            percussion_instruments: HashSet::new(),
         };
 
         // Act
@@ -46,4 +60,32 @@ mod tests {
         let expected = 2.0;
         assert_float_absolute_eq!(actual, expected);
     }
+
+    #[test]
+    fn test_get_measure_duration_seconds_compound_6_8_at_60_dotted_quarters() {
+        // Arrange
+        let measure_data = MeasureData {
+            number: "1".to_string(),
+            note_count: 6,
+            onset_count: 6,
+            chord_sizes: Vec::new(),
+            grace_note_count: 0,
+            tuplets: TupletUsage::default(),
+            tempo: Tempo::new(60.0, 1.5),
+            time_signature: TimeSignature::new(6, 8),
+            length_quarters: 3.0,
+            pitches: HashSet::new(),
+            sounding_pitches: HashSet::new(),
+            percussion_instruments: HashSet::new(),
+        };
+
+        // Act
+        let actual = measure_data.get_measure_duration_seconds();
+
+        // Assert
+        // 60 dotted quarters per minute = 1 second per beat
+        // 6/8 time = 2 dotted quarter beats per measure
+        let expected = 2.0;
+        assert_float_absolute_eq!(actual, expected);
+    }
 }
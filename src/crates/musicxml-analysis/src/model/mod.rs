@@ -1,11 +1,56 @@
 pub mod measure_data;
 pub use measure_data::MeasureData;
 
+pub mod interval;
+pub use interval::{ChromaticInterval, Interval, IntervalQuality};
+
 pub mod pitch;
 pub use pitch::{Accidental, NoteName, Pitch};
 
+pub mod rational_time;
+pub use rational_time::RationalTime;
+
 pub mod time_signature;
-pub use time_signature::TimeSignature;
+pub use time_signature::{BeatGrouping, TimeSignature};
+
+pub mod key_signature;
+pub use key_signature::{KeyChange, KeyMode, KeySignature};
+
+pub mod key;
+pub use key::Key;
+
+pub mod clef;
+pub use clef::{Clef, ClefChange, ClefSign};
+
+pub mod tempo;
+pub use tempo::Tempo;
+
+pub mod tempo_map;
+pub use tempo_map::{RampShape, TempoMap, TempoRamp, TempoSegment};
+
+pub mod tempo_source;
+pub use tempo_source::TempoSource;
+
+pub mod percussion_instrument;
+pub use percussion_instrument::PercussionInstrument;
 
 pub mod piano_key;
 pub use piano_key::{ChromaticNoteName, PianoKey};
+
+pub mod transposition;
+pub use transposition::Transposition;
+
+pub mod chord;
+pub use chord::{Chord, ChordKind, SeventhQuality, TriadQuality};
+
+pub mod note_event;
+pub use note_event::NoteEvent;
+
+pub mod note_kind;
+pub use note_kind::{GraceNote, NoteKind};
+
+pub mod tuplet;
+pub use tuplet::{TupletRatio, TupletUsage};
+
+pub mod timeline;
+pub use timeline::{MeasureFill, PartMeasure, Timeline, TimelineMeasure};
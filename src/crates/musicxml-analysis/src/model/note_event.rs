@@ -0,0 +1,84 @@
+use super::{NoteKind, PercussionInstrument, Pitch, RationalTime, TupletRatio};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NoteEvent {
+    pub part_id: String,
+    pub measure_index: usize,
+    pub voice: Option<String>,
+    pub staff: u32,
+    pub written_pitch: Pitch,         // display position for unpitched notes
+    pub sounding_pitch: Pitch,        // concert pitch, the written pitch outside transposing parts
+    pub doubled_pitch: Option<Pitch>, // sounding octave doubling from <double>
+    pub is_unpitched: bool,
+    pub instrument_id: Option<String>, // <instrument> of parts with more than one
+    pub onset: RationalTime,           // relative to the start of the measure
+    pub duration: RationalTime,
+    pub onset_seconds: f64,
+    pub duration_seconds: f64,
+    pub is_chord_member: bool, // also set for the first note of a chord
+    pub kind: NoteKind,
+    pub tuplets: Vec<TupletRatio>, // outermost first, empty outside tuplets
+}
+
+impl NoteEvent {
+    // Relative to the start of the measure, like the onset
+    pub fn end(&self) -> RationalTime {
+        self.onset + self.duration
+    }
+
+    // Factor of all tuplets together applied to the written duration
+    pub fn tuplet_factor(&self) -> f64 {
+        self.tuplets.iter().map(TupletRatio::time_factor).product()
+    }
+
+    pub fn percussion_instrument(&self) -> Option<PercussionInstrument> {
+        if !self.is_unpitched {
+            return None;
+        }
+        Some(match &self.instrument_id {
+            Some(instrument_id) => PercussionInstrument::Instrument(instrument_id.clone()),
+            None => PercussionInstrument::StaffPosition(self.part_id.clone(), self.written_pitch),
+        })
+    }
+
+    pub fn end_seconds(&self) -> f64 {
+        self.onset_seconds + self.duration_seconds
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_float_eq::assert_float_absolute_eq;
+
+    use super::*;
+    use crate::model::{Accidental, NoteName};
+
+    #[test]
+    fn test_end_seconds() {
+        // Arrange
+        let sut = NoteEvent {
+            part_id: "P1".to_string(),
+            measure_index: 0,
+            voice: None,
+            staff: 1,
+            written_pitch: Pitch::new(NoteName::C, 4, Accidental::Natural),
+            sounding_pitch: Pitch::new(NoteName::C, 4, Accidental::Natural),
+            doubled_pitch: None,
+            is_unpitched: false,
+            instrument_id: None,
+            onset: RationalTime::new(1, 4),
+            duration: RationalTime::new(1, 8),
+            onset_seconds: 0.5,
+            duration_seconds: 0.25,
+            is_chord_member: false,
+            kind: NoteKind::Regular,
+            tuplets: Vec::new(),
+        };
+
+        // Act
+        let actual = sut.end_seconds();
+
+        // Assert
+        assert_float_absolute_eq!(actual, 0.75);
+    }
+}
@@ -0,0 +1,15 @@
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum NoteKind {
+    #[default]
+    Regular,
+    Grace(GraceNote),
+    Cue, // printed for reference, another part plays it
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct GraceNote {
+    pub slash: bool,                       // acciaccatura
+    pub steal_time_previous: Option<f64>,  // percentage taken from the note before
+    pub steal_time_following: Option<f64>, // percentage taken from the note after
+    pub make_time: Option<f64>,            // quarters added between the notes around it
+}
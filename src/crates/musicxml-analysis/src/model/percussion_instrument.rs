@@ -0,0 +1,8 @@
+use super::Pitch;
+
+// A kit piece of an unpitched part
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PercussionInstrument {
+    Instrument(String),           // <instrument> id, unique within the score
+    StaffPosition(String, Pitch), // part id and display position, when no id is given
+}
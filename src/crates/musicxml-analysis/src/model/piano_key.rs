@@ -1,6 +1,12 @@
-use super::{Accidental, NoteName, Pitch};
+use std::cmp::Ordering;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+use super::Pitch;
+
+// A0, the lowest key of a standard piano
+const LOWEST_88_KEY_MIDI_NUMBER: i32 = 21;
+const KEYS_ON_88_KEY_KEYBOARD: i32 = 88;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum ChromaticNoteName {
     C,
     CSharp,
@@ -23,69 +29,67 @@ pub struct PianoKey {
 }
 
 impl PianoKey {
-    pub fn from_pitch(pitch: &Pitch) -> Self {
-        let (chromatic_note, octave_adjustment) =
-            convert_to_chromatic_note_with_octave(pitch.note_name, pitch.accidental);
-
-        Self {
-            note_name: chromatic_note,
-            octave: (pitch.octave as i8 + octave_adjustment) as u8,
+    // None when the pitch falls between keys or below octave 0. Pitches beyond
+    // A0 to C8 still get a key; index_on_88_key_keyboard tells them apart
+    pub fn from_pitch(pitch: &Pitch) -> Option<Self> {
+        let cents = pitch.accidental.cents();
+        if pitch.accidental.is_microtonal() {
+            return None;
         }
+
+        let semitone = pitch.note_name.semitones_above_c() + cents / 100;
+        let octave = pitch.octave as i32 + semitone.div_euclid(12);
+        Some(Self {
+            note_name: CHROMATIC_NOTE_NAMES[semitone.rem_euclid(12) as usize],
+            octave: u8::try_from(octave).ok()?,
+        })
+    }
+
+    // Like Pitch::midi_number, but not limited to 0..=127
+    pub fn midi_number(&self) -> i32 {
+        (self.octave as i32 + 1) * 12 + self.note_name as i32
+    }
+
+    // 0 for A0 up to 87 for C8, None for keys a standard piano does not have
+    pub fn index_on_88_key_keyboard(&self) -> Option<u8> {
+        let index = self.midi_number() - LOWEST_88_KEY_MIDI_NUMBER;
+        (0..KEYS_ON_88_KEY_KEYBOARD)
+            .contains(&index)
+            .then_some(index as u8)
     }
 }
 
-fn convert_to_chromatic_note_with_octave(
-    note_name: NoteName,
-    accidental: Accidental,
-) -> (ChromaticNoteName, i8) {
-    match (note_name, accidental) {
-        (NoteName::C, Accidental::DoubleFlat) => (ChromaticNoteName::ASharp, -1),
-        (NoteName::C, Accidental::Flat) => (ChromaticNoteName::B, -1),
-        (NoteName::C, Accidental::Natural) => (ChromaticNoteName::C, 0),
-        (NoteName::C, Accidental::Sharp) => (ChromaticNoteName::CSharp, 0),
-        (NoteName::C, Accidental::DoubleSharp) => (ChromaticNoteName::D, 0),
-
-        (NoteName::D, Accidental::DoubleFlat) => (ChromaticNoteName::C, 0),
-        (NoteName::D, Accidental::Flat) => (ChromaticNoteName::CSharp, 0),
-        (NoteName::D, Accidental::Natural) => (ChromaticNoteName::D, 0),
-        (NoteName::D, Accidental::Sharp) => (ChromaticNoteName::DSharp, 0),
-        (NoteName::D, Accidental::DoubleSharp) => (ChromaticNoteName::E, 0),
-
-        (NoteName::E, Accidental::DoubleFlat) => (ChromaticNoteName::D, 0),
-        (NoteName::E, Accidental::Flat) => (ChromaticNoteName::DSharp, 0),
-        (NoteName::E, Accidental::Natural) => (ChromaticNoteName::E, 0),
-        (NoteName::E, Accidental::Sharp) => (ChromaticNoteName::F, 0),
-        (NoteName::E, Accidental::DoubleSharp) => (ChromaticNoteName::FSharp, 0),
-
-        (NoteName::F, Accidental::DoubleFlat) => (ChromaticNoteName::DSharp, 0),
-        (NoteName::F, Accidental::Flat) => (ChromaticNoteName::E, 0),
-        (NoteName::F, Accidental::Natural) => (ChromaticNoteName::F, 0),
-        (NoteName::F, Accidental::Sharp) => (ChromaticNoteName::FSharp, 0),
-        (NoteName::F, Accidental::DoubleSharp) => (ChromaticNoteName::G, 0),
-
-        (NoteName::G, Accidental::DoubleFlat) => (ChromaticNoteName::F, 0),
-        (NoteName::G, Accidental::Flat) => (ChromaticNoteName::FSharp, 0),
-        (NoteName::G, Accidental::Natural) => (ChromaticNoteName::G, 0),
-        (NoteName::G, Accidental::Sharp) => (ChromaticNoteName::GSharp, 0),
-        (NoteName::G, Accidental::DoubleSharp) => (ChromaticNoteName::A, 0),
-
-        (NoteName::A, Accidental::DoubleFlat) => (ChromaticNoteName::G, 0),
-        (NoteName::A, Accidental::Flat) => (ChromaticNoteName::GSharp, 0),
-        (NoteName::A, Accidental::Natural) => (ChromaticNoteName::A, 0),
-        (NoteName::A, Accidental::Sharp) => (ChromaticNoteName::ASharp, 0),
-        (NoteName::A, Accidental::DoubleSharp) => (ChromaticNoteName::B, 0),
-
-        (NoteName::B, Accidental::DoubleFlat) => (ChromaticNoteName::A, 0),
-        (NoteName::B, Accidental::Flat) => (ChromaticNoteName::ASharp, 0),
-        (NoteName::B, Accidental::Natural) => (ChromaticNoteName::B, 0),
-        (NoteName::B, Accidental::Sharp) => (ChromaticNoteName::C, 1),
-        (NoteName::B, Accidental::DoubleSharp) => (ChromaticNoteName::CSharp, 1),
+impl Ord for PianoKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.midi_number().cmp(&other.midi_number())
     }
 }
 
+impl PartialOrd for PianoKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+const CHROMATIC_NOTE_NAMES: [ChromaticNoteName; 12] = [
+    ChromaticNoteName::C,
+    ChromaticNoteName::CSharp,
+    ChromaticNoteName::D,
+    ChromaticNoteName::DSharp,
+    ChromaticNoteName::E,
+    ChromaticNoteName::F,
+    ChromaticNoteName::FSharp,
+    ChromaticNoteName::G,
+    ChromaticNoteName::GSharp,
+    ChromaticNoteName::A,
+    ChromaticNoteName::ASharp,
+    ChromaticNoteName::B,
+];
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::model::{Accidental, NoteName};
     use rstest::rstest;
 
     #[rstest]
@@ -130,6 +134,9 @@ mod tests {
     #[case(NoteName::G, 4, Accidental::DoubleFlat, ChromaticNoteName::F, 4)]
     #[case(NoteName::A, 4, Accidental::DoubleFlat, ChromaticNoteName::G, 4)]
     #[case(NoteName::B, 4, Accidental::DoubleFlat, ChromaticNoteName::A, 4)]
+    // Triple sharps and flats
+    #[case(NoteName::B, 4, Accidental::TripleSharp, ChromaticNoteName::D, 5)]
+    #[case(NoteName::D, 4, Accidental::TripleFlat, ChromaticNoteName::B, 3)]
     fn test_piano_key_conversions(
         #[case] note_name: NoteName,
         #[case] octave: u8,
@@ -141,7 +148,7 @@ mod tests {
         let pitch = Pitch::new(note_name, octave, accidental);
 
         // Act
-        let piano_key = PianoKey::from_pitch(&pitch);
+        let piano_key = PianoKey::from_pitch(&pitch).unwrap();
 
         // Assert
         assert_eq!(piano_key.note_name, expected_chromatic);
@@ -158,11 +165,74 @@ mod tests {
     fn test_enharmonic_equivalents(#[case] enharmonic_group: Vec<(NoteName, u8, Accidental)>) {
         let piano_keys: Vec<PianoKey> = enharmonic_group
             .iter()
-            .map(|(note, octave, acc)| PianoKey::from_pitch(&Pitch::new(*note, *octave, *acc)))
+            .map(|(note, octave, acc)| {
+                PianoKey::from_pitch(&Pitch::new(*note, *octave, *acc)).unwrap()
+            })
             .collect();
 
         for i in 1..piano_keys.len() {
             assert_eq!(piano_keys[0], piano_keys[i]);
         }
     }
+
+    #[rstest]
+    #[case(Pitch::new(NoteName::E, 4, Accidental::QuarterFlat))]
+    #[case(Pitch::new(NoteName::F, 4, Accidental::ThreeQuarterSharp))]
+    #[case(Pitch::new(NoteName::G, 4, Accidental::Cents(-14)))]
+    // Below the lowest octave
+    #[case(Pitch::new(NoteName::C, 0, Accidental::Flat))]
+    fn test_piano_key_from_pitch_not_on_keyboard(#[case] pitch: Pitch) {
+        // Act
+        let actual = PianoKey::from_pitch(&pitch);
+
+        // Assert
+        assert_eq!(actual, None);
+    }
+
+    #[rstest]
+    #[case(ChromaticNoteName::A, 0, Some(0))]
+    #[case(ChromaticNoteName::C, 4, Some(39))]
+    #[case(ChromaticNoteName::C, 8, Some(87))]
+    #[case(ChromaticNoteName::GSharp, 0, None)]
+    #[case(ChromaticNoteName::CSharp, 8, None)]
+    fn test_index_on_88_key_keyboard(
+        #[case] note_name: ChromaticNoteName,
+        #[case] octave: u8,
+        #[case] expected: Option<u8>,
+    ) {
+        // Arrange
+        let sut = PianoKey { note_name, octave };
+
+        // Act
+        let actual = sut.index_on_88_key_keyboard();
+
+        // Assert
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_piano_keys_order_by_height() {
+        // Arrange
+        let mut keys = [
+            PianoKey {
+                note_name: ChromaticNoteName::C,
+                octave: 5,
+            },
+            PianoKey {
+                note_name: ChromaticNoteName::B,
+                octave: 4,
+            },
+            PianoKey {
+                note_name: ChromaticNoteName::CSharp,
+                octave: 4,
+            },
+        ];
+
+        // Act
+        keys.sort();
+
+        // Assert
+        let midi_numbers: Vec<i32> = keys.iter().map(PianoKey::midi_number).collect();
+        assert_eq!(midi_numbers, vec![61, 71, 72]);
+    }
 }
@@ -1,4 +1,8 @@
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+use std::cmp::Ordering;
+
+use super::Interval;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum NoteName {
     C,
     D,
@@ -9,13 +13,95 @@ pub enum NoteName {
     B,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+const NOTE_NAMES: [NoteName; 7] = [
+    NoteName::C,
+    NoteName::D,
+    NoteName::E,
+    NoteName::F,
+    NoteName::G,
+    NoteName::A,
+    NoteName::B,
+];
+
+impl NoteName {
+    // Steps above C in the same octave, 0 for C up to 6 for B
+    pub fn step_index(&self) -> i32 {
+        NOTE_NAMES.iter().position(|name| name == self).unwrap() as i32
+    }
+
+    pub fn from_step_index(step_index: i32) -> Self {
+        NOTE_NAMES[step_index.rem_euclid(7) as usize]
+    }
+
+    // Semitones above C in the same octave
+    pub fn semitones_above_c(&self) -> i32 {
+        match self {
+            NoteName::C => 0,
+            NoteName::D => 2,
+            NoteName::E => 4,
+            NoteName::F => 5,
+            NoteName::G => 7,
+            NoteName::A => 9,
+            NoteName::B => 11,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Accidental {
+    TripleFlat,
     DoubleFlat,
+    ThreeQuarterFlat,
     Flat,
+    QuarterFlat,
     Natural,
+    QuarterSharp,
     Sharp,
+    ThreeQuarterSharp,
     DoubleSharp,
+    TripleSharp,
+    Cents(i32), // any other alteration, build it with from_cents
+}
+
+impl Accidental {
+    pub fn from_cents(cents: i32) -> Self {
+        match cents {
+            -300 => Accidental::TripleFlat,
+            -200 => Accidental::DoubleFlat,
+            -150 => Accidental::ThreeQuarterFlat,
+            -100 => Accidental::Flat,
+            -50 => Accidental::QuarterFlat,
+            0 => Accidental::Natural,
+            50 => Accidental::QuarterSharp,
+            100 => Accidental::Sharp,
+            150 => Accidental::ThreeQuarterSharp,
+            200 => Accidental::DoubleSharp,
+            300 => Accidental::TripleSharp,
+            other => Accidental::Cents(other),
+        }
+    }
+
+    pub fn cents(&self) -> i32 {
+        match self {
+            Accidental::TripleFlat => -300,
+            Accidental::DoubleFlat => -200,
+            Accidental::ThreeQuarterFlat => -150,
+            Accidental::Flat => -100,
+            Accidental::QuarterFlat => -50,
+            Accidental::Natural => 0,
+            Accidental::QuarterSharp => 50,
+            Accidental::Sharp => 100,
+            Accidental::ThreeQuarterSharp => 150,
+            Accidental::DoubleSharp => 200,
+            Accidental::TripleSharp => 300,
+            Accidental::Cents(cents) => *cents,
+        }
+    }
+
+    // Between the keys of a 12-TET keyboard
+    pub fn is_microtonal(&self) -> bool {
+        self.cents() % 100 != 0
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -33,11 +119,75 @@ impl Pitch {
             accidental,
         }
     }
+
+    // Cents above C-1, so that C4 is 6000 like MIDI note 60
+    pub fn height_cents(&self) -> i32 {
+        (self.octave as i32 + 1) * 1200
+            + self.note_name.semitones_above_c() * 100
+            + self.accidental.cents()
+    }
+
+    // None for microtonal pitches and pitches outside the MIDI range
+    pub fn midi_number(&self) -> Option<u8> {
+        let height = self.height_cents();
+        if height % 100 != 0 {
+            return None;
+        }
+        u8::try_from(height / 100)
+            .ok()
+            .filter(|number| *number <= 127)
+    }
+
+    pub fn interval_to(&self, other: &Pitch) -> Interval {
+        Interval::new(
+            other.diatonic_index() - self.diatonic_index(),
+            other.height_cents() - self.height_cents(),
+        )
+    }
+
+    // Spelled by the steps of the interval: C4 up a minor third is E flat 4.
+    // Octaves below 0 are clamped, no notated pitch comes near them.
+    pub fn transposed(&self, interval: &Interval) -> Pitch {
+        let diatonic_index = self.diatonic_index() + interval.steps;
+        let mut pitch = Pitch::new(
+            NoteName::from_step_index(diatonic_index),
+            diatonic_index.div_euclid(7).clamp(0, u8::MAX as i32) as u8,
+            Accidental::Natural,
+        );
+        // Whatever the new letter does not cover goes into the accidental
+        let natural_cents = pitch.height_cents();
+        pitch.accidental =
+            Accidental::from_cents(self.height_cents() + interval.cents - natural_cents);
+        pitch
+    }
+
+    // Steps above C0
+    fn diatonic_index(&self) -> i32 {
+        self.octave as i32 * 7 + self.note_name.step_index()
+    }
+}
+
+// By height; enharmonic spellings of the same height are ordered by letter
+impl Ord for Pitch {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.height_cents()
+            .cmp(&other.height_cents())
+            .then(self.octave.cmp(&other.octave))
+            .then(self.note_name.cmp(&other.note_name))
+            .then(self.accidental.cmp(&other.accidental))
+    }
+}
+
+impl PartialOrd for Pitch {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rstest::rstest;
 
     #[test]
     fn test_pitch_creation() {
@@ -62,6 +212,38 @@ mod tests {
         assert_ne!(pitch1, pitch3);
     }
 
+    #[rstest]
+    #[case(-300, Accidental::TripleFlat)]
+    #[case(-150, Accidental::ThreeQuarterFlat)]
+    #[case(-50, Accidental::QuarterFlat)]
+    #[case(0, Accidental::Natural)]
+    #[case(150, Accidental::ThreeQuarterSharp)]
+    #[case(300, Accidental::TripleSharp)]
+    #[case(33, Accidental::Cents(33))]
+    fn test_accidental_from_cents(#[case] cents: i32, #[case] expected: Accidental) {
+        // Act
+        let actual = Accidental::from_cents(cents);
+
+        // Assert
+        assert_eq!(actual, expected);
+        assert_eq!(actual.cents(), cents);
+    }
+
+    #[rstest]
+    #[case(Accidental::DoubleSharp, false)]
+    #[case(Accidental::TripleFlat, false)]
+    #[case(Accidental::QuarterSharp, true)]
+    #[case(Accidental::ThreeQuarterFlat, true)]
+    #[case(Accidental::Cents(-400), false)]
+    #[case(Accidental::Cents(14), true)]
+    fn test_accidental_is_microtonal(#[case] accidental: Accidental, #[case] expected: bool) {
+        // Act
+        let actual = accidental.is_microtonal();
+
+        // Assert
+        assert_eq!(actual, expected);
+    }
+
     #[test]
     fn test_pitch_equality_with_unequal_accidentals() {
         // Arrange
@@ -74,4 +256,98 @@ mod tests {
         // Assert
         assert!(!result);
     }
+
+    #[rstest]
+    #[case(Pitch::new(NoteName::C, 4, Accidental::Natural), Some(60))]
+    #[case(Pitch::new(NoteName::A, 0, Accidental::Natural), Some(21))]
+    #[case(Pitch::new(NoteName::B, 3, Accidental::Sharp), Some(60))]
+    #[case(Pitch::new(NoteName::C, 0, Accidental::Flat), Some(11))]
+    #[case(Pitch::new(NoteName::E, 4, Accidental::QuarterFlat), None)]
+    #[case(Pitch::new(NoteName::A, 9, Accidental::Natural), None)]
+    fn test_midi_number(#[case] pitch: Pitch, #[case] expected: Option<u8>) {
+        // Act
+        let actual = pitch.midi_number();
+
+        // Assert
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_pitches_order_by_height() {
+        // Arrange
+        let c4 = Pitch::new(NoteName::C, 4, Accidental::Natural);
+        let b_sharp_3 = Pitch::new(NoteName::B, 3, Accidental::Sharp);
+        let e_quarter_flat_4 = Pitch::new(NoteName::E, 4, Accidental::QuarterFlat);
+        let e_flat_4 = Pitch::new(NoteName::E, 4, Accidental::Flat);
+        let mut pitches = vec![e_quarter_flat_4, c4, e_flat_4, b_sharp_3];
+
+        // Act
+        pitches.sort();
+
+        // Assert
+        assert_eq!(pitches, vec![b_sharp_3, c4, e_flat_4, e_quarter_flat_4]);
+    }
+
+    #[rstest]
+    // Minor third against augmented second of the same size
+    #[case(
+        Pitch::new(NoteName::C, 4, Accidental::Natural),
+        Pitch::new(NoteName::E, 4, Accidental::Flat),
+        Interval::from_semitones(2, 3)
+    )]
+    #[case(
+        Pitch::new(NoteName::C, 4, Accidental::Natural),
+        Pitch::new(NoteName::D, 4, Accidental::Sharp),
+        Interval::from_semitones(1, 3)
+    )]
+    #[case(
+        Pitch::new(NoteName::G, 4, Accidental::Natural),
+        Pitch::new(NoteName::C, 4, Accidental::Natural),
+        Interval::from_semitones(-4, -7)
+    )]
+    #[case(
+        Pitch::new(NoteName::B, 3, Accidental::Natural),
+        Pitch::new(NoteName::C, 5, Accidental::Natural),
+        Interval::from_semitones(8, 13)
+    )]
+    fn test_interval_to(#[case] from: Pitch, #[case] to: Pitch, #[case] expected: Interval) {
+        // Act
+        let actual = from.interval_to(&to);
+
+        // Assert
+        assert_eq!(actual, expected);
+    }
+
+    #[rstest]
+    #[case(
+        Pitch::new(NoteName::C, 4, Accidental::Natural),
+        Interval::from_semitones(2, 3),
+        Pitch::new(NoteName::E, 4, Accidental::Flat)
+    )]
+    #[case(
+        Pitch::new(NoteName::C, 4, Accidental::Natural),
+        Interval::from_semitones(1, 3),
+        Pitch::new(NoteName::D, 4, Accidental::Sharp)
+    )]
+    #[case(
+        Pitch::new(NoteName::F, 4, Accidental::Sharp),
+        Interval::from_semitones(-4, -7),
+        Pitch::new(NoteName::B, 3, Accidental::Natural)
+    )]
+    #[case(
+        Pitch::new(NoteName::B, 4, Accidental::Flat),
+        Interval::from_semitones(3, 6),
+        Pitch::new(NoteName::E, 5, Accidental::Natural)
+    )]
+    fn test_transposed_preserves_spelling(
+        #[case] pitch: Pitch,
+        #[case] interval: Interval,
+        #[case] expected: Pitch,
+    ) {
+        // Act
+        let actual = pitch.transposed(&interval);
+
+        // Assert
+        assert_eq!(actual, expected);
+    }
 }
@@ -0,0 +1,221 @@
+use std::{
+    cmp::Ordering,
+    fmt,
+    ops::{Add, AddAssign, Mul, Sub},
+};
+
+use super::TempoMap;
+
+// An exact length or position in whole notes, so that a triplet eighth is 1/12
+// and not 0.0833... Always kept in lowest terms with a positive denominator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RationalTime {
+    numerator: i64,
+    denominator: i64,
+}
+
+impl RationalTime {
+    pub const ZERO: RationalTime = RationalTime {
+        numerator: 0,
+        denominator: 1,
+    };
+
+    pub fn new(numerator: i64, denominator: i64) -> Self {
+        assert!(denominator != 0, "RationalTime with a zero denominator");
+        let divisor = gcd(numerator, denominator) * denominator.signum();
+        Self {
+            numerator: numerator / divisor,
+            denominator: denominator / divisor,
+        }
+    }
+
+    pub fn from_quarters(quarters: i64) -> Self {
+        Self::new(quarters, 4)
+    }
+
+    // A MusicXML duration, in divisions per quarter note
+    pub fn from_divisions(duration: i64, divisions: u32) -> Self {
+        Self::new(duration, 4 * divisions as i64)
+    }
+
+    pub fn numerator(&self) -> i64 {
+        self.numerator
+    }
+
+    pub fn denominator(&self) -> i64 {
+        self.denominator
+    }
+
+    pub fn to_whole_notes(&self) -> f64 {
+        self.numerator as f64 / self.denominator as f64
+    }
+
+    pub fn to_quarters(&self) -> f64 {
+        4.0 * self.to_whole_notes()
+    }
+
+    // Taken as a position from the start of the piece
+    pub fn to_seconds(&self, tempo_map: &TempoMap) -> f64 {
+        tempo_map.seconds_at(self.to_quarters())
+    }
+}
+
+impl Default for RationalTime {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl Add for RationalTime {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        let denominator = lcm(self.denominator, other.denominator);
+        Self::new(
+            self.numerator * (denominator / self.denominator)
+                + other.numerator * (denominator / other.denominator),
+            denominator,
+        )
+    }
+}
+
+impl AddAssign for RationalTime {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for RationalTime {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self + Self::new(-other.numerator, other.denominator)
+    }
+}
+
+// Scaling, as a tuplet does to the written length
+impl Mul for RationalTime {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        let left = gcd(self.numerator, other.denominator);
+        let right = gcd(other.numerator, self.denominator);
+        Self::new(
+            (self.numerator / left) * (other.numerator / right),
+            (self.denominator / right) * (other.denominator / left),
+        )
+    }
+}
+
+impl Ord for RationalTime {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.numerator as i128 * other.denominator as i128)
+            .cmp(&(other.numerator as i128 * self.denominator as i128))
+    }
+}
+
+impl PartialOrd for RationalTime {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for RationalTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.denominator == 1 {
+            write!(f, "{}", self.numerator)
+        } else {
+            write!(f, "{}/{}", self.numerator, self.denominator)
+        }
+    }
+}
+
+// Never 0, so that dividing by it is always safe
+fn gcd(a: i64, b: i64) -> i64 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a.max(1)
+}
+
+fn lcm(a: i64, b: i64) -> i64 {
+    a / gcd(a, b) * b
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_float_eq::assert_float_absolute_eq;
+    use rstest::rstest;
+
+    use super::*;
+    use crate::model::{Tempo, TempoSource};
+
+    #[rstest]
+    #[case(RationalTime::new(2, 8), 1, 4)]
+    #[case(RationalTime::new(3, -6), -1, 2)]
+    #[case(RationalTime::new(0, 5), 0, 1)]
+    #[case(RationalTime::from_divisions(6, 2), 3, 4)]
+    fn test_new_normalizes(
+        #[case] sut: RationalTime,
+        #[case] expected_numerator: i64,
+        #[case] expected_denominator: i64,
+    ) {
+        // Assert
+        assert_eq!(sut.numerator(), expected_numerator);
+        assert_eq!(sut.denominator(), expected_denominator);
+    }
+
+    #[test]
+    fn test_three_triplet_eighths_make_exactly_a_quarter() {
+        // Arrange
+        let triplet_eighth = RationalTime::new(1, 8) * RationalTime::new(2, 3);
+
+        // Act
+        let actual = triplet_eighth + triplet_eighth + triplet_eighth;
+
+        // Assert
+        assert_eq!(triplet_eighth, RationalTime::new(1, 12));
+        assert_eq!(actual, RationalTime::from_quarters(1));
+    }
+
+    #[rstest]
+    #[case(RationalTime::new(1, 3), RationalTime::new(1, 4), Ordering::Greater)]
+    #[case(RationalTime::new(2, 6), RationalTime::new(1, 3), Ordering::Equal)]
+    #[case(RationalTime::new(-1, 2), RationalTime::ZERO, Ordering::Less)]
+    fn test_ordering(
+        #[case] left: RationalTime,
+        #[case] right: RationalTime,
+        #[case] expected: Ordering,
+    ) {
+        // Act
+        let actual = left.cmp(&right);
+
+        // Assert
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_subtraction_and_display() {
+        // Act
+        let actual = RationalTime::new(1, 2) - RationalTime::new(1, 12);
+
+        // Assert
+        assert_eq!(actual.to_string(), "5/12");
+        assert_eq!(RationalTime::from_quarters(8).to_string(), "2");
+    }
+
+    #[test]
+    fn test_to_seconds_under_tempo_map() {
+        // Arrange
+        // 120 quarter notes per minute, half a second per quarter
+        let tempo_map = TempoMap::new(Tempo::quarter_notes(120.0), TempoSource::Default);
+        let sut = RationalTime::new(1, 12);
+
+        // Act
+        let actual = sut.to_seconds(&tempo_map);
+
+        // Assert
+        assert_float_absolute_eq!(actual, 1.0 / 6.0);
+    }
+}
@@ -0,0 +1,86 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tempo {
+    pub beats_per_minute: f64,
+    pub beat_unit: f64, // length of one beat in quarter notes, 1.5 for a dotted quarter
+}
+
+// Playback default when a score gives no tempo at all
+impl Default for Tempo {
+    fn default() -> Self {
+        Self::quarter_notes(120.0)
+    }
+}
+
+impl Tempo {
+    pub fn new(beats_per_minute: f64, beat_unit: f64) -> Self {
+        Self {
+            beats_per_minute,
+            beat_unit,
+        }
+    }
+
+    pub fn quarter_notes(beats_per_minute: f64) -> Self {
+        Self::new(beats_per_minute, 1.0)
+    }
+
+    pub fn quarter_notes_per_minute(&self) -> f64 {
+        self.beats_per_minute * self.beat_unit
+    }
+
+    pub fn seconds_per_quarter_note(&self) -> f64 {
+        60.0 / self.quarter_notes_per_minute()
+    }
+
+    pub fn scaled(&self, factor: f64) -> Self {
+        Self::new(self.beats_per_minute * factor, self.beat_unit)
+    }
+
+    // "old unit = new unit": the new unit takes over the duration of the old one
+    pub fn metric_modulation(&self, old_unit: f64, new_unit: f64) -> Self {
+        Self::new(self.quarter_notes_per_minute() / old_unit, new_unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_float_eq::assert_float_absolute_eq;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Tempo::quarter_notes(120.0), 0.5)]
+    #[case(Tempo::new(60.0, 2.0), 0.5)]
+    #[case(Tempo::new(120.0, 0.5), 1.0)]
+    #[case(Tempo::new(60.0, 1.5), 60.0 / 90.0)]
+    fn test_seconds_per_quarter_note(#[case] sut: Tempo, #[case] expected: f64) {
+        // Act
+        let actual = sut.seconds_per_quarter_note();
+
+        // Assert
+        assert_float_absolute_eq!(actual, expected);
+    }
+
+    #[rstest]
+    // quarter = dotted quarter: three eighths in the time of two
+    #[case(Tempo::quarter_notes(120.0), 1.0, 1.5, 180.0)]
+    // dotted quarter = quarter: from 6/8 into 2/4 with the same beat
+    #[case(Tempo::new(60.0, 1.5), 1.5, 1.0, 60.0)]
+    // quarter = half: the written notes get twice as fast
+    #[case(Tempo::quarter_notes(80.0), 1.0, 2.0, 160.0)]
+    fn test_metric_modulation(
+        #[case] sut: Tempo,
+        #[case] old_unit: f64,
+        #[case] new_unit: f64,
+        #[case] expected_quarter_notes_per_minute: f64,
+    ) {
+        // Act
+        let actual = sut.metric_modulation(old_unit, new_unit);
+
+        // Assert
+        assert_float_absolute_eq!(
+            actual.quarter_notes_per_minute(),
+            expected_quarter_notes_per_minute
+        );
+        assert_float_absolute_eq!(actual.beat_unit, new_unit);
+    }
+}
@@ -0,0 +1,391 @@
+use super::{Tempo, TempoSource};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RampShape {
+    #[default]
+    Linear, // tempo changes by the same amount every quarter note
+    Exponential, // tempo changes by the same ratio every quarter note
+}
+
+impl RampShape {
+    fn quarter_notes_per_minute_at(&self, start: f64, end: f64, progress: f64) -> f64 {
+        match self {
+            RampShape::Linear => start + (end - start) * progress,
+            RampShape::Exponential => start * (end / start).powf(progress),
+        }
+    }
+
+    // Seconds spent on the first `progress` part of a ramp `length_quarters` long
+    fn seconds(&self, start: f64, end: f64, length_quarters: f64, progress: f64) -> f64 {
+        if start == end {
+            return 60.0 * length_quarters * progress / start;
+        }
+
+        match self {
+            RampShape::Linear => {
+                let reached = self.quarter_notes_per_minute_at(start, end, progress);
+                60.0 * length_quarters / (end - start) * (reached / start).ln()
+            }
+            RampShape::Exponential => {
+                let ratio = end / start;
+                60.0 * length_quarters / (start * ratio.ln()) * (1.0 - ratio.powf(-progress))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TempoRamp {
+    pub end_tempo: Tempo, // reached at the start of the next segment
+    pub shape: RampShape,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TempoSegment {
+    pub start_quarters: f64, // position in quarter notes from the start of the piece
+    pub tempo: Tempo,        // at the start of the segment
+    pub ramp: Option<TempoRamp>,
+    pub source: TempoSource,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TempoMap {
+    segments: Vec<TempoSegment>, // sorted by start, the first one starts at 0
+}
+
+impl Default for TempoMap {
+    fn default() -> Self {
+        Self::new(Tempo::default(), TempoSource::Default)
+    }
+}
+
+impl TempoMap {
+    pub fn new(initial_tempo: Tempo, source: TempoSource) -> Self {
+        Self {
+            segments: vec![TempoSegment {
+                start_quarters: 0.0,
+                tempo: initial_tempo,
+                ramp: None,
+                source,
+            }],
+        }
+    }
+
+    pub fn segments(&self) -> &[TempoSegment] {
+        &self.segments
+    }
+
+    pub fn set_tempo(&mut self, start_quarters: f64, tempo: Tempo, source: TempoSource) {
+        let start_quarters = start_quarters.max(0.0);
+        let previous = &self.segments[self.segment_index_at(start_quarters)];
+
+        // Restating the tempo in effect is not a change
+        if previous.start_quarters == start_quarters
+            || previous.ramp.is_some()
+            || previous.tempo != tempo
+        {
+            self.insert_segment(TempoSegment {
+                start_quarters,
+                tempo,
+                ramp: None,
+                source,
+            });
+        }
+    }
+
+    // Gradually moves from the tempo in effect at the start to `end_tempo` at the end
+    pub fn set_ramp(
+        &mut self,
+        start_quarters: f64,
+        end_quarters: f64,
+        end_tempo: Tempo,
+        shape: RampShape,
+        source: TempoSource,
+    ) {
+        let start_quarters = start_quarters.max(0.0);
+        if end_quarters <= start_quarters {
+            return;
+        }
+
+        let start_tempo = self.tempo_at(start_quarters);
+        self.segments.retain(|segment| {
+            segment.start_quarters <= start_quarters || segment.start_quarters > end_quarters
+        });
+        self.insert_segment(TempoSegment {
+            start_quarters,
+            tempo: start_tempo,
+            ramp: Some(TempoRamp { end_tempo, shape }),
+            source: source.clone(),
+        });
+        self.insert_segment(TempoSegment {
+            start_quarters: end_quarters,
+            tempo: end_tempo,
+            ramp: None,
+            source,
+        });
+    }
+
+    pub fn tempo_at(&self, position_quarters: f64) -> Tempo {
+        let index = self.segment_index_at(position_quarters);
+        let segment = &self.segments[index];
+
+        match (segment.ramp, self.segments.get(index + 1)) {
+            (Some(ramp), Some(next)) => {
+                let progress = (position_quarters - segment.start_quarters)
+                    / (next.start_quarters - segment.start_quarters);
+                let quarter_notes_per_minute = ramp.shape.quarter_notes_per_minute_at(
+                    segment.tempo.quarter_notes_per_minute(),
+                    ramp.end_tempo.quarter_notes_per_minute(),
+                    progress,
+                );
+                Tempo::new(
+                    quarter_notes_per_minute / segment.tempo.beat_unit,
+                    segment.tempo.beat_unit,
+                )
+            }
+            _ => segment.tempo,
+        }
+    }
+
+    pub fn seconds_at(&self, position_quarters: f64) -> f64 {
+        let mut seconds = 0.0;
+        for (index, segment) in self.segments.iter().enumerate() {
+            if segment.start_quarters >= position_quarters {
+                break;
+            }
+
+            let next = self.segments.get(index + 1);
+            let end_quarters = next.map_or(position_quarters, |next| {
+                next.start_quarters.min(position_quarters)
+            });
+            seconds += match (segment.ramp, next) {
+                (Some(ramp), Some(next)) => {
+                    let length_quarters = next.start_quarters - segment.start_quarters;
+                    ramp.shape.seconds(
+                        segment.tempo.quarter_notes_per_minute(),
+                        ramp.end_tempo.quarter_notes_per_minute(),
+                        length_quarters,
+                        (end_quarters - segment.start_quarters) / length_quarters,
+                    )
+                }
+                _ => {
+                    (end_quarters - segment.start_quarters)
+                        * segment.tempo.seconds_per_quarter_note()
+                }
+            };
+        }
+        seconds
+    }
+
+    // The constant tempo that covers the range in the same number of seconds
+    pub fn average_tempo(&self, start_quarters: f64, end_quarters: f64) -> Tempo {
+        let changes_inside = self.segments.iter().any(|segment| {
+            segment.start_quarters > start_quarters && segment.start_quarters < end_quarters
+        });
+        let segment = &self.segments[self.segment_index_at(start_quarters)];
+        if !changes_inside && segment.ramp.is_none() {
+            return segment.tempo;
+        }
+
+        let seconds = self.seconds_at(end_quarters) - self.seconds_at(start_quarters);
+        Tempo::quarter_notes((end_quarters - start_quarters) * 60.0 / seconds)
+    }
+
+    fn segment_index_at(&self, position_quarters: f64) -> usize {
+        self.segments
+            .partition_point(|segment| segment.start_quarters <= position_quarters)
+            .saturating_sub(1)
+    }
+
+    fn insert_segment(&mut self, segment: TempoSegment) {
+        let index = self
+            .segments
+            .partition_point(|existing| existing.start_quarters < segment.start_quarters);
+
+        match self.segments.get_mut(index) {
+            Some(existing) if existing.start_quarters == segment.start_quarters => {
+                *existing = segment
+            }
+            _ => self.segments.insert(index, segment),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_float_eq::assert_float_absolute_eq;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(0.0, 0.0)]
+    #[case(2.0, 1.0)]
+    #[case(4.0, 2.0)]
+    #[case(6.0, 4.0)] // after the change to 60 quarters per minute
+    #[case(10.0, 7.0)] // after the change to dotted quarters at 80
+    fn test_seconds_at(#[case] position_quarters: f64, #[case] expected: f64) {
+        // Arrange
+        let sut = create_tempo_map_with_two_changes();
+
+        // Act
+        let actual = sut.seconds_at(position_quarters);
+
+        // Assert
+        assert_float_absolute_eq!(actual, expected);
+    }
+
+    fn create_tempo_map_with_two_changes() -> TempoMap {
+        let mut tempo_map = TempoMap::new(Tempo::quarter_notes(120.0), TempoSource::Default);
+        tempo_map.set_tempo(8.0, Tempo::new(80.0, 1.5), TempoSource::Metronome);
+        tempo_map.set_tempo(4.0, Tempo::quarter_notes(60.0), TempoSource::Metronome);
+        tempo_map
+    }
+
+    #[test]
+    fn test_tempo_at() {
+        // Arrange
+        let sut = create_tempo_map_with_two_changes();
+
+        // Act
+        let actual: Vec<Tempo> = [0.0, 3.9, 4.0, 8.5]
+            .iter()
+            .map(|&position| sut.tempo_at(position))
+            .collect();
+
+        // Assert
+        let expected = vec![
+            Tempo::quarter_notes(120.0),
+            Tempo::quarter_notes(120.0),
+            Tempo::quarter_notes(60.0),
+            Tempo::new(80.0, 1.5),
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_set_tempo_replaces_change_at_same_position() {
+        // Arrange
+        let mut sut = TempoMap::new(Tempo::quarter_notes(120.0), TempoSource::Default);
+
+        // Act
+        sut.set_tempo(0.0, Tempo::quarter_notes(90.0), TempoSource::Metronome);
+
+        // Assert
+        assert_eq!(sut.segments().len(), 1);
+        assert_eq!(sut.tempo_at(0.0), Tempo::quarter_notes(90.0));
+    }
+
+    #[test]
+    fn test_set_tempo_ignores_restated_tempo() {
+        // Arrange
+        let mut sut = TempoMap::new(Tempo::new(60.0, 1.5), TempoSource::Default);
+
+        // Act
+        sut.set_tempo(3.0, Tempo::new(60.0, 1.5), TempoSource::Metronome);
+
+        // Assert
+        assert_eq!(sut.segments().len(), 1);
+    }
+
+    #[test]
+    fn test_average_tempo_without_change_keeps_beat_unit() {
+        // Arrange
+        let sut = create_tempo_map_with_two_changes();
+
+        // Act
+        let actual = sut.average_tempo(8.0, 11.0);
+
+        // Assert
+        assert_eq!(actual, Tempo::new(80.0, 1.5));
+    }
+
+    #[rstest]
+    // 4 quarters slowing from 120 to 60: 240 / 60 * ln(120 / reached) seconds
+    #[case(RampShape::Linear, 4.0, 4.0 * 2.0_f64.ln())]
+    #[case(RampShape::Linear, 2.0, 4.0 * (120.0_f64 / 90.0).ln())]
+    // Exponential: 240 / (120 * ln 2) * (2^progress - 1)
+    #[case(RampShape::Exponential, 4.0, 2.0 / 2.0_f64.ln())]
+    #[case(RampShape::Exponential, 2.0, 2.0 / 2.0_f64.ln() * (2.0_f64.sqrt() - 1.0))]
+    fn test_seconds_at_inside_ramp(
+        #[case] shape: RampShape,
+        #[case] ramp_quarters_covered: f64,
+        #[case] expected_ramp_seconds: f64,
+    ) {
+        // Arrange
+        let mut sut = TempoMap::new(Tempo::quarter_notes(120.0), TempoSource::Default);
+        sut.set_ramp(
+            4.0,
+            8.0,
+            Tempo::quarter_notes(60.0),
+            shape,
+            TempoSource::Text("rit.".to_string()),
+        );
+
+        // Act
+        let actual = sut.seconds_at(4.0 + ramp_quarters_covered);
+
+        // Assert
+        assert_float_absolute_eq!(actual, 2.0 + expected_ramp_seconds);
+    }
+
+    #[test]
+    fn test_tempo_at_inside_and_after_ramp() {
+        // Arrange
+        let mut sut = TempoMap::new(Tempo::new(60.0, 1.5), TempoSource::Default);
+        sut.set_ramp(
+            0.0,
+            6.0,
+            Tempo::new(30.0, 1.5),
+            RampShape::Linear,
+            TempoSource::Text("rit.".to_string()),
+        );
+
+        // Act
+        let actual: Vec<Tempo> = [0.0, 3.0, 6.0, 9.0]
+            .iter()
+            .map(|&position| sut.tempo_at(position))
+            .collect();
+
+        // Assert
+        let expected = vec![
+            Tempo::new(60.0, 1.5),
+            Tempo::new(45.0, 1.5),
+            Tempo::new(30.0, 1.5),
+            Tempo::new(30.0, 1.5),
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_set_tempo_after_ramp_start_is_a_change() {
+        // Arrange
+        let mut sut = TempoMap::new(Tempo::quarter_notes(120.0), TempoSource::Default);
+        sut.set_ramp(
+            0.0,
+            4.0,
+            Tempo::quarter_notes(60.0),
+            RampShape::Linear,
+            TempoSource::Text("rit.".to_string()),
+        );
+
+        // Act
+        sut.set_tempo(4.0, Tempo::quarter_notes(120.0), TempoSource::Metronome);
+
+        // Assert
+        assert_eq!(sut.segments().len(), 2);
+        assert_eq!(sut.tempo_at(4.0), Tempo::quarter_notes(120.0));
+    }
+
+    #[test]
+    fn test_average_tempo_across_change() {
+        // Arrange
+        let sut = create_tempo_map_with_two_changes();
+
+        // Act
+        let actual = sut.average_tempo(2.0, 6.0);
+
+        // Assert
+        // Two quarters at 120 and two at 60 take three seconds
+        assert_float_absolute_eq!(actual.quarter_notes_per_minute(), 80.0);
+    }
+}
@@ -0,0 +1,39 @@
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum TempoSource {
+    #[default]
+    Default, // the score gives no tempo, playback default
+    Metronome,    // <metronome> mark, including metric modulations
+    Sound,        // <sound tempo="..."/>
+    Text(String), // written words such as "Allegro" or "rit."
+}
+
+impl fmt::Display for TempoSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TempoSource::Default => write!(f, "default"),
+            TempoSource::Metronome => write!(f, "metronome"),
+            TempoSource::Sound => write!(f, "sound"),
+            TempoSource::Text(text) => write!(f, "text \"{text}\""),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(TempoSource::Default, "default")]
+    #[case(TempoSource::Metronome, "metronome")]
+    #[case(TempoSource::Text("Allegro".to_string()), "text \"Allegro\"")]
+    fn test_tempo_source_display(#[case] sut: TempoSource, #[case] expected: &str) {
+        // Act
+        let actual = sut.to_string();
+
+        // Assert
+        assert_eq!(actual, expected);
+    }
+}
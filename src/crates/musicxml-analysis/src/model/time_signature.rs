@@ -1,7 +1,27 @@
-#[derive(Debug, Clone, Copy, PartialEq)]
+use super::{RationalTime, Tempo};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BeatGrouping {
+    Simple,             // 2/4, 3/4, 4/4: every written unit is a beat
+    Compound,           // 6/8, 9/8, 12/8: beats are dotted, three units each
+    Additive(Vec<u32>), // 3+2+2/8: beats of unequal length
+}
+
+impl BeatGrouping {
+    pub fn from_numerator(numerator: u32) -> Self {
+        if numerator > 3 && numerator.is_multiple_of(3) {
+            BeatGrouping::Compound
+        } else {
+            BeatGrouping::Simple
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TimeSignature {
     pub numerator: u32,   // 3 in "3/4"
     pub denominator: u32, // 4 in "3/4"
+    pub grouping: BeatGrouping,
 }
 
 impl TimeSignature {
@@ -9,17 +29,65 @@ impl TimeSignature {
         Self {
             numerator,
             denominator,
+            grouping: BeatGrouping::from_numerator(numerator),
+        }
+    }
+
+    pub fn additive(groups: Vec<u32>, denominator: u32) -> Self {
+        Self {
+            numerator: groups.iter().sum(),
+            denominator,
+            grouping: BeatGrouping::Additive(groups),
         }
     }
 
     pub fn beats_per_measure(&self) -> u32 {
-        self.numerator
+        match &self.grouping {
+            BeatGrouping::Simple => self.numerator,
+            BeatGrouping::Compound => self.numerator / 3,
+            BeatGrouping::Additive(groups) => groups.len() as u32,
+        }
+    }
+
+    pub fn beat_lengths_in_quarters(&self) -> Vec<f64> {
+        let unit = self.unit_in_quarters();
+        match &self.grouping {
+            BeatGrouping::Simple => vec![unit; self.numerator as usize],
+            BeatGrouping::Compound => vec![unit * 3.0; (self.numerator / 3) as usize],
+            BeatGrouping::Additive(groups) => groups.iter().map(|&g| unit * g as f64).collect(),
+        }
+    }
+
+    // Additive meters count in their written unit, the beats themselves differ
+    pub fn beat_unit_in_quarters(&self) -> f64 {
+        match &self.grouping {
+            BeatGrouping::Compound => self.unit_in_quarters() * 3.0,
+            _ => self.unit_in_quarters(),
+        }
+    }
+
+    pub fn measure_length(&self) -> RationalTime {
+        RationalTime::new(self.numerator as i64, self.denominator as i64)
+    }
+
+    pub fn measure_length_in_quarters(&self) -> f64 {
+        self.numerator as f64 * self.unit_in_quarters()
+    }
+
+    pub fn measure_duration_seconds(&self, tempo: &Tempo) -> f64 {
+        self.measure_length_in_quarters() * tempo.seconds_per_quarter_note()
+    }
+
+    fn unit_in_quarters(&self) -> f64 {
+        4.0 / self.denominator as f64
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use assert_float_eq::assert_float_absolute_eq;
+    use rstest::rstest;
 
     #[test]
     fn test_time_signature_beats_per_measure() {
@@ -33,4 +101,116 @@ mod tests {
         let expected = 3;
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn test_time_signature_measure_duration_seconds() {
+        // Arrange
+        let sut = TimeSignature::new(3, 4);
+
+        // Act
+        let actual = sut.measure_duration_seconds(&Tempo::quarter_notes(60.0));
+
+        // Assert
+        let expected = 3.0;
+        assert_eq!(actual, expected);
+    }
+
+    #[rstest]
+    #[case(TimeSignature::new(2, 4), BeatGrouping::Simple, 2)]
+    #[case(TimeSignature::new(3, 4), BeatGrouping::Simple, 3)]
+    #[case(TimeSignature::new(4, 4), BeatGrouping::Simple, 4)]
+    #[case(TimeSignature::new(2, 2), BeatGrouping::Simple, 2)]
+    #[case(TimeSignature::new(3, 8), BeatGrouping::Simple, 3)]
+    #[case(TimeSignature::new(5, 4), BeatGrouping::Simple, 5)]
+    #[case(TimeSignature::new(6, 8), BeatGrouping::Compound, 2)]
+    #[case(TimeSignature::new(9, 8), BeatGrouping::Compound, 3)]
+    #[case(TimeSignature::new(12, 8), BeatGrouping::Compound, 4)]
+    #[case(TimeSignature::new(6, 4), BeatGrouping::Compound, 2)]
+    #[case(TimeSignature::additive(vec![3, 2, 2], 8), BeatGrouping::Additive(vec![3, 2, 2]), 3)]
+    fn test_time_signature_grouping_and_beats(
+        #[case] sut: TimeSignature,
+        #[case] expected_grouping: BeatGrouping,
+        #[case] expected_beats: u32,
+    ) {
+        // Act
+        let actual_beats = sut.beats_per_measure();
+
+        // Assert
+        assert_eq!(sut.grouping, expected_grouping);
+        assert_eq!(actual_beats, expected_beats);
+    }
+
+    #[rstest]
+    #[case(TimeSignature::new(4, 4), 4.0)]
+    #[case(TimeSignature::new(3, 4), 3.0)]
+    #[case(TimeSignature::new(2, 2), 4.0)]
+    #[case(TimeSignature::new(3, 2), 6.0)]
+    #[case(TimeSignature::new(6, 8), 3.0)]
+    #[case(TimeSignature::new(9, 8), 4.5)]
+    #[case(TimeSignature::new(12, 8), 6.0)]
+    #[case(TimeSignature::new(5, 8), 2.5)]
+    #[case(TimeSignature::new(7, 16), 1.75)]
+    #[case(TimeSignature::additive(vec![3, 2, 2], 8), 3.5)]
+    fn test_time_signature_measure_length_in_quarters(
+        #[case] sut: TimeSignature,
+        #[case] expected: f64,
+    ) {
+        // Act
+        let actual = sut.measure_length_in_quarters();
+
+        // Assert
+        assert_float_absolute_eq!(actual, expected);
+    }
+
+    #[rstest]
+    // 120 quarters per minute: 6/8 holds three quarters
+    #[case(TimeSignature::new(6, 8), Tempo::quarter_notes(120.0), 1.5)]
+    // 60 dotted quarters per minute: two beats of one second each
+    #[case(TimeSignature::new(6, 8), Tempo::new(60.0, 1.5), 2.0)]
+    // Alla breve at 60 half notes per minute
+    #[case(TimeSignature::new(2, 2), Tempo::new(60.0, 2.0), 2.0)]
+    #[case(TimeSignature::new(2, 2), Tempo::quarter_notes(120.0), 2.0)]
+    #[case(TimeSignature::new(3, 8), Tempo::new(120.0, 0.5), 1.5)]
+    #[case(TimeSignature::new(12, 8), Tempo::new(40.0, 1.5), 6.0)]
+    #[case(TimeSignature::additive(vec![3, 2, 2], 8), Tempo::new(240.0, 0.5), 1.75)]
+    fn test_time_signature_measure_duration_seconds_for_common_meters(
+        #[case] sut: TimeSignature,
+        #[case] tempo: Tempo,
+        #[case] expected: f64,
+    ) {
+        // Act
+        let actual = sut.measure_duration_seconds(&tempo);
+
+        // Assert
+        assert_float_absolute_eq!(actual, expected);
+    }
+
+    #[rstest]
+    #[case(TimeSignature::new(4, 4), 1.0)]
+    #[case(TimeSignature::new(2, 2), 2.0)]
+    #[case(TimeSignature::new(6, 8), 1.5)]
+    #[case(TimeSignature::new(3, 8), 0.5)]
+    #[case(TimeSignature::additive(vec![3, 2, 2], 8), 0.5)]
+    fn test_time_signature_beat_unit_in_quarters(
+        #[case] sut: TimeSignature,
+        #[case] expected: f64,
+    ) {
+        // Act
+        let actual = sut.beat_unit_in_quarters();
+
+        // Assert
+        assert_float_absolute_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_time_signature_beat_lengths_in_quarters() {
+        // Arrange
+        let sut = TimeSignature::additive(vec![3, 2, 2], 8);
+
+        // Act
+        let actual = sut.beat_lengths_in_quarters();
+
+        // Assert
+        assert_eq!(actual, vec![1.5, 1.0, 1.0]);
+    }
 }
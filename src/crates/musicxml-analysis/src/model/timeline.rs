@@ -0,0 +1,453 @@
+use std::collections::{HashMap, HashSet};
+
+use super::{
+    ClefChange, KeyChange, MeasureData, NoteEvent, NoteKind, RationalTime, Tempo, TempoMap,
+    TempoSegment, TimeSignature, TupletUsage,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeasureFill {
+    Complete,
+    Incomplete,
+    Overfull,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartMeasure {
+    pub part_id: String,
+    pub fill: MeasureFill,
+    pub key_changes: Vec<KeyChange>, // written in this measure, by position
+    pub clef_changes: Vec<ClefChange>, // written in this measure, by position
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimelineMeasure {
+    pub number: String,
+    pub tempo: Tempo, // averaged over the measure when the tempo changes inside it
+    pub time_signature: TimeSignature,
+    pub start: RationalTime,  // position on the axis of the tempo map
+    pub length: RationalTime, // notated content when it differs from the time signature
+    pub start_seconds: f64,
+    pub implicit: bool, // pickup or split measure, not counted in the measure numbering
+    pub parts: Vec<PartMeasure>,
+}
+
+impl TimelineMeasure {
+    pub fn start_quarters(&self) -> f64 {
+        self.start.to_quarters()
+    }
+
+    pub fn length_in_quarters(&self) -> f64 {
+        self.length.to_quarters()
+    }
+
+    pub fn end(&self) -> RationalTime {
+        self.start + self.length
+    }
+
+    pub fn duration_seconds(&self) -> f64 {
+        self.length_in_quarters() * self.tempo.seconds_per_quarter_note()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Timeline {
+    pub measures: Vec<TimelineMeasure>,
+    pub notes: Vec<NoteEvent>,
+    pub tempo_map: TempoMap,
+}
+
+impl Timeline {
+    pub fn part_ids(&self) -> Vec<&str> {
+        let mut part_ids: Vec<&str> = Vec::new();
+        for part in self.measures.iter().flat_map(|measure| &measure.parts) {
+            if !part_ids.contains(&part.part_id.as_str()) {
+                part_ids.push(&part.part_id);
+            }
+        }
+        part_ids
+    }
+
+    // Implicit measures are short on purpose and not reported
+    pub fn irregular_measures(&self) -> impl Iterator<Item = (&TimelineMeasure, &PartMeasure)> {
+        self.measures
+            .iter()
+            .filter(|m| !m.implicit)
+            .flat_map(|measure| {
+                measure
+                    .parts
+                    .iter()
+                    .filter(|part| part.fill != MeasureFill::Complete)
+                    .map(move |part| (measure, part))
+            })
+    }
+
+    // Every tempo segment along with the measure it starts in
+    pub fn tempo_sections(&self) -> impl Iterator<Item = (&TimelineMeasure, &TempoSegment)> {
+        self.tempo_map.segments().iter().filter_map(|segment| {
+            let measure_index = self
+                .measures
+                .partition_point(|measure| measure.start_quarters() <= segment.start_quarters)
+                .checked_sub(1)?;
+            Some((&self.measures[measure_index], segment))
+        })
+    }
+
+    pub fn to_measure_data(&self) -> Vec<MeasureData> {
+        self.build_measure_data(|_| true)
+    }
+
+    pub fn to_part_measure_data(&self, part_id: &str) -> Vec<MeasureData> {
+        self.build_measure_data(|note| note.part_id == part_id)
+    }
+
+    fn build_measure_data<F>(&self, include_note: F) -> Vec<MeasureData>
+    where
+        F: Fn(&NoteEvent) -> bool,
+    {
+        let mut measure_data: Vec<MeasureData> = self
+            .measures
+            .iter()
+            .map(|measure| MeasureData {
+                number: measure.number.clone(),
+                note_count: 0,
+                onset_count: 0,
+                chord_sizes: Vec::new(),
+                grace_note_count: 0,
+                tuplets: TupletUsage::default(),
+                tempo: measure.tempo,
+                time_signature: measure.time_signature.clone(),
+                length_quarters: measure.length_in_quarters(),
+                pitches: HashSet::new(),
+                sounding_pitches: HashSet::new(),
+                percussion_instruments: HashSet::new(),
+            })
+            .collect();
+
+        let mut onsets: HashSet<(usize, RationalTime)> = HashSet::new();
+        let mut chords: HashMap<(usize, &str, Option<&str>, u32, RationalTime), u32> =
+            HashMap::new();
+        let mut notes_by_measure: Vec<Vec<&NoteEvent>> = vec![Vec::new(); self.measures.len()];
+        for note in self.notes.iter().filter(|note| include_note(note)) {
+            let data = &mut measure_data[note.measure_index];
+            if note.kind == NoteKind::Cue {
+                continue;
+            }
+            match note.percussion_instrument() {
+                Some(instrument) => {
+                    data.percussion_instruments.insert(instrument);
+                }
+                None => {
+                    data.pitches.insert(note.written_pitch);
+                    data.sounding_pitches.insert(note.sounding_pitch);
+                    data.sounding_pitches.extend(note.doubled_pitch);
+                }
+            }
+            if let NoteKind::Grace(_) = note.kind {
+                data.grace_note_count += 1;
+                continue;
+            }
+            data.note_count += 1;
+            notes_by_measure[note.measure_index].push(note);
+
+            if !note.tuplets.is_empty() {
+                data.tuplets.note_count += 1;
+                data.tuplets.ratios.extend(&note.tuplets);
+            }
+            if note.tuplets.len() > 1 {
+                data.tuplets.nested_note_count += 1;
+            }
+
+            // Notes starting together in any part are a single attack
+            let onset = note.onset;
+            if onsets.insert((note.measure_index, onset)) {
+                data.onset_count += 1;
+            }
+            if note.is_chord_member {
+                let voice = note.voice.as_deref();
+                let chord = (
+                    note.measure_index,
+                    note.part_id.as_str(),
+                    voice,
+                    note.staff,
+                    onset,
+                );
+                *chords.entry(chord).or_default() += 1;
+            }
+        }
+
+        let mut chords: Vec<_> = chords.into_iter().collect();
+        chords.sort_unstable();
+        // A chord whose other notes are all tied over is no longer a chord
+        for ((measure_index, ..), size) in chords.into_iter().filter(|&(_, size)| size > 1) {
+            measure_data[measure_index].chord_sizes.push(size);
+        }
+
+        for (data, notes) in measure_data.iter_mut().zip(&notes_by_measure) {
+            data.tuplets.cross_rhythm = has_cross_rhythm(notes);
+        }
+
+        measure_data
+    }
+}
+
+// A note of another voice, with a different tuplet, starts while a tuplet note sounds
+fn has_cross_rhythm(notes: &[&NoteEvent]) -> bool {
+    notes
+        .iter()
+        .filter(|note| !note.tuplets.is_empty())
+        .any(|tuplet_note| {
+            notes.iter().any(|other| {
+                voice_of(other) != voice_of(tuplet_note)
+                    && other.tuplets != tuplet_note.tuplets
+                    && other.onset > tuplet_note.onset
+                    && other.onset < tuplet_note.end()
+            })
+        })
+}
+
+fn voice_of(note: &NoteEvent) -> (&str, Option<&str>, u32) {
+    (&note.part_id, note.voice.as_deref(), note.staff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Accidental, NoteName, Pitch, TempoSource};
+
+    #[test]
+    fn test_to_measure_data_empty_timeline() {
+        // Arrange
+        let sut = Timeline::default();
+
+        // Act
+        let actual = sut.to_measure_data();
+
+        // Assert
+        assert!(actual.is_empty());
+    }
+
+    #[test]
+    fn test_to_measure_data_groups_notes_by_measure() {
+        // Arrange
+        let c4 = Pitch::new(NoteName::C, 4, Accidental::Natural);
+        let d4 = Pitch::new(NoteName::D, 4, Accidental::Natural);
+        let sut = Timeline {
+            measures: vec![create_measure("1", 0.0), create_measure("2", 2.0)],
+            notes: vec![
+                create_note(0, c4, 0.0),
+                create_note(0, c4, 1.0),
+                create_note(1, d4, 2.0),
+            ],
+            tempo_map: TempoMap::default(),
+        };
+
+        // Act
+        let actual = sut.to_measure_data();
+
+        // Assert
+        let expected = vec![
+            MeasureData {
+                number: "1".to_string(),
+                note_count: 2,
+                onset_count: 2,
+                chord_sizes: Vec::new(),
+                grace_note_count: 0,
+                tuplets: TupletUsage::default(),
+                tempo: Tempo::quarter_notes(120.0),
+                time_signature: TimeSignature::new(4, 4),
+                length_quarters: 4.0,
+                pitches: HashSet::from([c4]),
+                sounding_pitches: HashSet::from([c4]),
+                percussion_instruments: HashSet::new(),
+            },
+            MeasureData {
+                number: "2".to_string(),
+                note_count: 1,
+                onset_count: 1,
+                chord_sizes: Vec::new(),
+                grace_note_count: 0,
+                tuplets: TupletUsage::default(),
+                tempo: Tempo::quarter_notes(120.0),
+                time_signature: TimeSignature::new(4, 4),
+                length_quarters: 4.0,
+                pitches: HashSet::from([d4]),
+                sounding_pitches: HashSet::from([d4]),
+                percussion_instruments: HashSet::new(),
+            },
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_to_measure_data_keeps_empty_measures() {
+        // Arrange
+        let sut = Timeline {
+            measures: vec![create_measure("1", 0.0)],
+            notes: vec![],
+            tempo_map: TempoMap::default(),
+        };
+
+        // Act
+        let actual = sut.to_measure_data();
+
+        // Assert
+        assert_eq!(actual.len(), 1);
+        assert_eq!(actual[0].note_count, 0);
+    }
+
+    #[test]
+    fn test_irregular_measures_skips_complete_measures() {
+        // Arrange
+        let mut pickup = create_measure("0", 0.0);
+        pickup.parts[0].fill = MeasureFill::Incomplete;
+        pickup.implicit = true;
+        let mut second_measure = create_measure("2", 2.0);
+        second_measure.parts[0].fill = MeasureFill::Incomplete;
+        let sut = Timeline {
+            measures: vec![pickup, create_measure("1", 0.0), second_measure],
+            notes: vec![],
+            tempo_map: TempoMap::default(),
+        };
+
+        // Act
+        let actual: Vec<(&str, &str)> = sut
+            .irregular_measures()
+            .map(|(measure, part)| (measure.number.as_str(), part.part_id.as_str()))
+            .collect();
+
+        // Assert
+        assert_eq!(actual, vec![("2", "P1")]);
+    }
+
+    #[test]
+    fn test_tempo_sections_start_in_measure_containing_change() {
+        // Arrange
+        let mut tempo_map = TempoMap::default();
+        tempo_map.set_tempo(5.0, Tempo::quarter_notes(90.0), TempoSource::Metronome);
+        let sut = Timeline {
+            measures: vec![create_measure("1", 0.0), create_measure("2", 2.0)],
+            notes: vec![],
+            tempo_map,
+        };
+
+        // Act
+        let actual: Vec<(&str, &TempoSource)> = sut
+            .tempo_sections()
+            .map(|(measure, segment)| (measure.number.as_str(), &segment.source))
+            .collect();
+
+        // Assert
+        assert_eq!(
+            actual,
+            vec![("1", &TempoSource::Default), ("2", &TempoSource::Metronome)]
+        );
+    }
+
+    #[test]
+    fn test_to_part_measure_data_only_counts_notes_of_part() {
+        // Arrange
+        let c4 = Pitch::new(NoteName::C, 4, Accidental::Natural);
+        let mut other_part_note = create_note(0, c4, 0.0);
+        other_part_note.part_id = "P2".to_string();
+        let mut measure = create_measure("1", 0.0);
+        measure.parts.push(PartMeasure {
+            part_id: "P2".to_string(),
+            fill: MeasureFill::Complete,
+            key_changes: Vec::new(),
+            clef_changes: Vec::new(),
+        });
+        let sut = Timeline {
+            measures: vec![measure],
+            notes: vec![
+                create_note(0, c4, 0.0),
+                other_part_note.clone(),
+                other_part_note,
+            ],
+            tempo_map: TempoMap::default(),
+        };
+
+        // Act
+        let ensemble = sut.to_measure_data();
+        let first_part = sut.to_part_measure_data("P1");
+        let second_part = sut.to_part_measure_data("P2");
+
+        // Assert
+        assert_eq!(sut.part_ids(), vec!["P1", "P2"]);
+        assert_eq!(ensemble[0].note_count, 3);
+        assert_eq!(first_part[0].note_count, 1);
+        assert_eq!(second_part[0].note_count, 2);
+    }
+
+    #[test]
+    fn test_to_measure_data_counts_chords_as_one_onset() {
+        // Arrange
+        let c4 = Pitch::new(NoteName::C, 4, Accidental::Natural);
+        let e4 = Pitch::new(NoteName::E, 4, Accidental::Natural);
+        let g4 = Pitch::new(NoteName::G, 4, Accidental::Natural);
+        let chord: Vec<NoteEvent> = [c4, e4, g4]
+            .into_iter()
+            .map(|pitch| NoteEvent {
+                is_chord_member: true,
+                ..create_note(0, pitch, 0.0)
+            })
+            .collect();
+        let mut other_part_note = create_note(0, c4, 0.0);
+        other_part_note.part_id = "P2".to_string();
+        let mut notes = chord;
+        notes.push(other_part_note);
+        notes.push(create_note(0, e4, 1.0));
+        let sut = Timeline {
+            measures: vec![create_measure("1", 0.0)],
+            notes,
+            tempo_map: TempoMap::default(),
+        };
+
+        // Act
+        let actual = sut.to_measure_data();
+
+        // Assert
+        assert_eq!(actual[0].note_count, 5);
+        assert_eq!(actual[0].onset_count, 2);
+        assert_eq!(actual[0].chord_sizes, vec![3]);
+    }
+
+    fn create_measure(number: &str, start_seconds: f64) -> TimelineMeasure {
+        TimelineMeasure {
+            number: number.to_string(),
+            tempo: Tempo::quarter_notes(120.0),
+            time_signature: TimeSignature::new(4, 4),
+            start: RationalTime::from_quarters((start_seconds * 2.0) as i64),
+            length: RationalTime::from_quarters(4),
+            start_seconds,
+            implicit: false,
+            parts: vec![PartMeasure {
+                part_id: "P1".to_string(),
+                fill: MeasureFill::Complete,
+                key_changes: Vec::new(),
+                clef_changes: Vec::new(),
+            }],
+        }
+    }
+
+    fn create_note(measure_index: usize, pitch: Pitch, onset_seconds: f64) -> NoteEvent {
+        NoteEvent {
+            part_id: "P1".to_string(),
+            measure_index,
+            voice: None,
+            staff: 1,
+            written_pitch: pitch,
+            sounding_pitch: pitch,
+            doubled_pitch: None,
+            is_unpitched: false,
+            instrument_id: None,
+            onset: RationalTime::from_quarters((onset_seconds * 2.0) as i64), // at 120 BPM
+            duration: RationalTime::from_quarters(1),
+            onset_seconds,
+            duration_seconds: 0.5,
+            is_chord_member: false,
+            kind: NoteKind::Regular,
+            tuplets: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use super::{Interval, Pitch};
+
+// From written to sounding pitch, as given by <transpose>
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Transposition {
+    pub diatonic: i32,       // steps, octave change excluded
+    pub chromatic: i32,      // semitones, octave change excluded
+    pub octave_change: i32,  // octaves added on top of diatonic and chromatic
+    pub double: Option<i32>, // octaves from the sounding pitch to its doubling
+}
+
+impl Transposition {
+    // Spelled with the nearest number of steps when the score gives no diatonic
+    pub fn new(diatonic: Option<i32>, chromatic: i32, octave_change: i32) -> Self {
+        Self {
+            diatonic: diatonic.unwrap_or((chromatic as f64 * 7.0 / 12.0).round() as i32),
+            chromatic,
+            octave_change,
+            double: None,
+        }
+    }
+
+    pub fn sounding_pitch(&self, written: &Pitch) -> Pitch {
+        written.transposed(&Interval::from_semitones(
+            self.diatonic + 7 * self.octave_change,
+            self.chromatic + 12 * self.octave_change,
+        ))
+    }
+
+    pub fn doubled_pitch(&self, written: &Pitch) -> Option<Pitch> {
+        let sounding = self.sounding_pitch(written);
+        self.double.map(|octaves| {
+            sounding.transposed(&Interval::from_semitones(7 * octaves, 12 * octaves))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Accidental, NoteName};
+    use rstest::rstest;
+
+    #[rstest]
+    // Clarinet in B flat: a written D sounds a major second lower
+    #[case(
+        Transposition::new(Some(-1), -2, 0),
+        Pitch::new(NoteName::D, 5, Accidental::Natural),
+        Pitch::new(NoteName::C, 5, Accidental::Natural)
+    )]
+    #[case(
+        Transposition::new(Some(-1), -2, 0),
+        Pitch::new(NoteName::C, 5, Accidental::Natural),
+        Pitch::new(NoteName::B, 4, Accidental::Flat)
+    )]
+    // Horn in F: a perfect fifth lower
+    #[case(
+        Transposition::new(Some(-4), -7, 0),
+        Pitch::new(NoteName::F, 4, Accidental::Sharp),
+        Pitch::new(NoteName::B, 3, Accidental::Natural)
+    )]
+    // Guitar: an octave lower through octave-change alone
+    #[case(
+        Transposition::new(Some(0), 0, -1),
+        Pitch::new(NoteName::E, 4, Accidental::Natural),
+        Pitch::new(NoteName::E, 3, Accidental::Natural)
+    )]
+    // Alto saxophone without diatonic: a major sixth lower, spelled from the semitones
+    #[case(
+        Transposition::new(None, -9, 0),
+        Pitch::new(NoteName::A, 4, Accidental::Natural),
+        Pitch::new(NoteName::C, 4, Accidental::Natural)
+    )]
+    // Quarter tones keep their offset
+    #[case(
+        Transposition::new(Some(-1), -2, 0),
+        Pitch::new(NoteName::E, 4, Accidental::QuarterFlat),
+        Pitch::new(NoteName::D, 4, Accidental::QuarterFlat)
+    )]
+    fn test_sounding_pitch(
+        #[case] transposition: Transposition,
+        #[case] written: Pitch,
+        #[case] expected: Pitch,
+    ) {
+        // Act
+        let actual = transposition.sounding_pitch(&written);
+
+        // Assert
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_doubled_pitch_is_an_octave_from_the_sounding_pitch() {
+        // Arrange
+        let transposition = Transposition {
+            double: Some(-1),
+            ..Transposition::new(Some(-1), -2, 0)
+        };
+        let written = Pitch::new(NoteName::D, 4, Accidental::Natural);
+
+        // Act
+        let actual = transposition.doubled_pitch(&written);
+
+        // Assert
+        assert_eq!(
+            actual,
+            Some(Pitch::new(NoteName::C, 3, Accidental::Natural))
+        );
+    }
+}
@@ -0,0 +1,94 @@
+use std::{collections::HashSet, fmt};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TupletRatio {
+    pub actual_notes: u32, // 3 in a triplet
+    pub normal_notes: u32, // 2 in a triplet
+}
+
+impl TupletRatio {
+    pub fn new(actual_notes: u32, normal_notes: u32) -> Self {
+        Self {
+            actual_notes,
+            normal_notes,
+        }
+    }
+
+    // Factor applied to the written duration, 2/3 for a triplet
+    pub fn time_factor(&self) -> f64 {
+        self.normal_notes as f64 / self.actual_notes as f64
+    }
+
+    // The ratio left of this one after the outer ratio is taken out, if it divides evenly
+    pub fn within(&self, outer: TupletRatio) -> Option<TupletRatio> {
+        if outer.actual_notes == 0
+            || outer.normal_notes == 0
+            || !self.actual_notes.is_multiple_of(outer.actual_notes)
+            || !self.normal_notes.is_multiple_of(outer.normal_notes)
+        {
+            return None;
+        }
+        Some(TupletRatio::new(
+            self.actual_notes / outer.actual_notes,
+            self.normal_notes / outer.normal_notes,
+        ))
+    }
+}
+
+impl fmt::Display for TupletRatio {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.actual_notes, self.normal_notes)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TupletUsage {
+    pub note_count: u32,        // notes inside at least one tuplet
+    pub nested_note_count: u32, // notes inside a tuplet inside another tuplet
+    pub ratios: HashSet<TupletRatio>,
+    pub cross_rhythm: bool, // another voice starts a note inside a tuplet note, like 3 against 4
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_float_eq::assert_float_absolute_eq;
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(TupletRatio::new(3, 2), 2.0 / 3.0)]
+    #[case(TupletRatio::new(5, 4), 0.8)]
+    #[case(TupletRatio::new(2, 3), 1.5)] // duplet in compound time
+    fn test_time_factor(#[case] ratio: TupletRatio, #[case] expected: f64) {
+        // Act
+        let actual = ratio.time_factor();
+
+        // Assert
+        assert_float_absolute_eq!(actual, expected);
+    }
+
+    #[rstest]
+    #[case(
+        TupletRatio::new(9, 4),
+        TupletRatio::new(3, 2),
+        Some(TupletRatio::new(3, 2))
+    )]
+    #[case(
+        TupletRatio::new(15, 8),
+        TupletRatio::new(3, 2),
+        Some(TupletRatio::new(5, 4))
+    )]
+    #[case(TupletRatio::new(5, 4), TupletRatio::new(3, 2), None)]
+    fn test_within(
+        #[case] combined: TupletRatio,
+        #[case] outer: TupletRatio,
+        #[case] expected: Option<TupletRatio>,
+    ) {
+        // Act
+        let actual = combined.within(outer);
+
+        // Assert
+        assert_eq!(actual, expected);
+    }
+}